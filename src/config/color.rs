@@ -316,4 +316,8 @@ impl Color {
 	pub fn get(&self, index: u8) -> &Rgba<f64> {
 		&self.table[&index]
 	}
+
+	pub fn set(&mut self, index: u8, value: Rgba<f64>) {
+		self.table.insert(index, value);
+	}
 }
@@ -22,9 +22,12 @@ pub struct Environment {
 	program: Option<String>,
 	term:    Option<String>,
 
-	cache:  usize,
-	scroll: usize,
-	batch:  Option<u32>,
+	cache:       usize,
+	scroll:      usize,
+	batch:       Option<u32>,
+	synchronize: u32,
+	operations:  Vec<String>,
+	title:       String,
 
 	x11:   X11,
 	cocoa: Cocoa,
@@ -36,9 +39,12 @@ impl Default for Environment {
 			program: None,
 			term:    None,
 
-			cache:  4096,
-			scroll: 4096,
-			batch:  Some(16),
+			cache:       4096,
+			scroll:      4096,
+			batch:       Some(16),
+			synchronize: 150,
+			operations:  vec!["report".into(), "stack".into()],
+			title:       "{title}".into(),
 
 			x11:   Default::default(),
 			cocoa: Default::default(),
@@ -120,6 +126,27 @@ impl Environment {
 			}
 		}
 
+		// Negative or huge values are ignored, they would wrap around otherwise.
+		if let Some(value) = table.get("synchronize").and_then(|v| v.as_integer()).and_then(|v| u32::try_from(v).ok()) {
+			self.synchronize = value;
+		}
+
+		if let Some(value) = table.get("title").and_then(|v| v.as_str()) {
+			self.title = value.into();
+		}
+
+		if let Some(value) = table.get("operations") {
+			match *value {
+				Value::Array(ref value) =>
+					self.operations = value.iter().filter_map(|v| v.as_str()).map(String::from).collect(),
+
+				Value::Boolean(false) =>
+					self.operations.clear(),
+
+				_ => ()
+			}
+		}
+
 		if let Some(table) = table.get("x11").and_then(|v| v.as_table()) {
 			if let Some(value) = table.get("display").and_then(|v| v.as_str()) {
 				self.x11.display = Some(value.into());
@@ -157,6 +184,25 @@ impl Environment {
 		self.batch
 	}
 
+	/// How long to hold back rendering during a synchronized update, in
+	/// milliseconds.
+	pub fn synchronize(&self) -> u32 {
+		self.synchronize
+	}
+
+	/// Whether the given window operation is allowed, the operations are
+	/// `iconify`, `raise`, `resize`, `maximize`, `report`, `title` and `stack`.
+	pub fn allows(&self, operation: &str) -> bool {
+		self.operations.iter().any(|o| o == operation)
+	}
+
+	/// The window title template, `{title}` and `{icon}` are replaced with the
+	/// ones set by the program, `{directory}` with the working directory and
+	/// `{process}` with the name of the foreground process.
+	pub fn title(&self) -> &str {
+		&self.title
+	}
+
 	pub fn x11(&self) -> &X11 {
 		&self.x11
 	}
@@ -20,17 +20,25 @@ use crate::platform::{Key, key};
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct Input {
-	prefix: Key,
-	mouse:  bool,
-	locale: Option<String>,
+	prefix:    Key,
+	mouse:     bool,
+	locale:    Option<String>,
+	clipboard: bool,
+	files:     bool,
+	spawn:     Option<Key>,
+	save:      Option<Key>,
 }
 
 impl Default for Input {
 	fn default() -> Self {
 		Input {
-			prefix: Key::new("a".to_string().into(), key::LOGO, Default::default()),
-			mouse:  true,
-			locale: None,
+			prefix:    Key::new("a".to_string().into(), key::LOGO, Default::default()),
+			mouse:     true,
+			locale:    None,
+			clipboard: false,
+			files:     false,
+			spawn:     None,
+			save:      Some(Key::new("s".to_string().into(), key::LOGO, Default::default())),
 		}
 	}
 }
@@ -48,6 +56,22 @@ impl Input {
 		if let Some(value) = table.get("locale").and_then(|v| v.as_str()) {
 			self.locale = Some(value.into());
 		}
+
+		if let Some(value) = table.get("clipboard").and_then(|v| v.as_bool()) {
+			self.clipboard = value;
+		}
+
+		if let Some(value) = table.get("files").and_then(|v| v.as_bool()) {
+			self.files = value;
+		}
+
+		if let Some(value) = table.get("spawn").and_then(|v| v.as_str()) {
+			self.spawn = Some(to_key(value));
+		}
+
+		if let Some(value) = table.get("save").and_then(|v| v.as_str()) {
+			self.save = Some(to_key(value));
+		}
 	}
 
 	pub fn prefix(&self) -> &Key {
@@ -61,6 +85,27 @@ impl Input {
 	pub fn locale(&self) -> Option<&str> {
 		self.locale.as_ref().map(AsRef::as_ref)
 	}
+
+	/// Whether programs are allowed to read the clipboard.
+	pub fn clipboard(&self) -> bool {
+		self.clipboard
+	}
+
+	/// Whether programs are allowed to send images by file name.
+	pub fn files(&self) -> bool {
+		self.files
+	}
+
+	/// The key to open a new window in the current directory.
+	pub fn spawn(&self) -> Option<&Key> {
+		self.spawn.as_ref()
+	}
+
+	/// The key to save the last file sent by a program, files are saved in
+	/// `cancer-<uid>` within the temporary directory.
+	pub fn save(&self) -> Option<&Key> {
+		self.save.as_ref()
+	}
 }
 
 fn to_key<T: AsRef<str>>(value: T) -> Key {
@@ -106,9 +106,17 @@ impl Config {
 		&self.style
 	}
 
+	pub fn style_mut(&mut self) -> &mut Style {
+		&mut self.style
+	}
+
 	pub fn color(&self) -> &Color {
 		&self.color
 	}
+
+	pub fn color_mut(&mut self) -> &mut Color {
+		&mut self.color
+	}
 }
 
 impl<'a> From<&'a toml::value::Table> for Config {
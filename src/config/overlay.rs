@@ -43,13 +43,17 @@ impl Default for Overlay {
 			status: Some(Style {
 				foreground: to_color("#000"),
 				background: to_color("#c0c0c0"),
+				underline:  None,
 				attributes: style::NONE,
+				palette:    [None; 3],
 			}),
 
 			selection: Style {
 				foreground: to_color("#000"),
 				background: to_color("#c0c0c0"),
+				underline:  None,
 				attributes: style::NONE,
+				palette:    [None; 3],
 			},
 
 			hinter:  Default::default(),
@@ -77,7 +81,9 @@ impl Default for Hinter {
 			style:  Style {
 				foreground: to_color("#000"),
 				background: to_color("#c0c0c0"),
+				underline:  None,
 				attributes: style::BOLD,
+				palette:    [None; 3],
 			},
 		}
 	}
@@ -240,6 +240,10 @@ impl Style {
 		&self.color
 	}
 
+	pub fn color_mut(&mut self) -> &mut Color {
+		&mut self.color
+	}
+
 	pub fn cursor(&self) -> &Cursor {
 		&self.cursor
 	}
@@ -261,6 +265,14 @@ impl Color {
 	pub fn strikethrough(&self) -> Option<&Rgba<f64>> {
 		self.strikethrough.as_ref()
 	}
+
+	pub fn set_foreground(&mut self, value: Rgba<f64>) {
+		self.foreground = value;
+	}
+
+	pub fn set_background(&mut self, value: Rgba<f64>) {
+		self.background = value;
+	}
 }
 
 impl Cursor {
@@ -60,6 +60,44 @@ pub fn to_color(arg: &str) -> Option<Rgba<f64>> {
 	Some(Rgba::new_u8(r, g, b, a))
 }
 
+/// Parse an X11 color specification, either `rgb:r/g/b` with 1 to 4 hex
+/// digits per channel or any of the `#` forms.
+pub fn to_rgb(arg: &str) -> Option<Rgba<f64>> {
+	if !arg.starts_with("rgb:") {
+		return to_color(arg);
+	}
+
+	let mut channels = [0.0; 3];
+	let mut parts    = arg[4..].split('/');
+
+	for channel in &mut channels {
+		let part = parts.next()?;
+
+		if part.is_empty() || part.len() > 4 {
+			return None;
+		}
+
+		let value = u16::from_str_radix(part, 16).ok()?;
+		let max   = (1u32 << (part.len() * 4)) - 1;
+
+		*channel = value as f64 / max as f64;
+	}
+
+	if parts.next().is_some() {
+		return None;
+	}
+
+	Some(Rgba::new(channels[0], channels[1], channels[2], 1.0))
+}
+
+/// Format a color as an X11 `rgb:rrrr/gggg/bbbb` specification.
+pub fn from_rgb(color: &Rgba<f64>) -> String {
+	format!("rgb:{:04x}/{:04x}/{:04x}",
+		(color.red   * 65535.0).round() as u16,
+		(color.green * 65535.0).round() as u16,
+		(color.blue  * 65535.0).round() as u16)
+}
+
 pub fn to_attributes(arg: &str) -> style::Attributes {
 	let mut attributes = style::NONE;
 
@@ -73,6 +111,11 @@ pub fn to_attributes(arg: &str) -> style::Attributes {
 			"reverse"   => style::REVERSE,
 			"invisible" => style::INVISIBLE,
 			"struck"    => style::STRUCK,
+			"double"    => style::DOUBLE,
+			"curly"     => style::CURLY,
+			"dotted"    => style::DOTTED,
+			"dashed"    => style::DASHED,
+			"overline"  => style::OVERLINE,
 			_           => style::NONE,
 		}
 	}
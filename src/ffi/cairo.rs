@@ -16,7 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::os::raw::c_void;
-use libc::{c_int, c_uchar, c_double};
+use libc::{c_int, c_char, c_uchar, c_double};
 
 #[repr(C)]
 pub struct cairo_t(c_void);
@@ -115,7 +115,19 @@ extern "C" {
 
 	pub fn cairo_move_to(cr: *mut cairo_t, x: c_double, y: c_double);
 	pub fn cairo_line_to(cr: *mut cairo_t, x: c_double, y: c_double);
+	pub fn cairo_curve_to(cr: *mut cairo_t, x1: c_double, y1: c_double, x2: c_double, y2: c_double, x3: c_double, y3: c_double);
+	pub fn cairo_arc(cr: *mut cairo_t, xc: c_double, yc: c_double, radius: c_double, angle1: c_double, angle2: c_double);
+	pub fn cairo_arc_negative(cr: *mut cairo_t, xc: c_double, yc: c_double, radius: c_double, angle1: c_double, angle2: c_double);
+	pub fn cairo_get_current_point(cr: *mut cairo_t, x: *mut c_double, y: *mut c_double);
 	pub fn cairo_set_line_width(cr: *mut cairo_t, w: c_double);
+	pub fn cairo_set_dash(cr: *mut cairo_t, dashes: *const c_double, num_dashes: c_int, offset: c_double);
+
+	pub fn cairo_select_font_face(cr: *mut cairo_t, family: *const c_char, slant: c_int, weight: c_int);
+	pub fn cairo_set_font_size(cr: *mut cairo_t, size: c_double);
+	pub fn cairo_show_text(cr: *mut cairo_t, text: *const c_char);
+
+	pub fn cairo_translate(cr: *mut cairo_t, tx: c_double, ty: c_double);
+	pub fn cairo_scale(cr: *mut cairo_t, sx: c_double, sy: c_double);
 
 	pub fn cairo_clip(cr: *mut cairo_t);
 	pub fn cairo_rectangle(cr: *mut cairo_t, x: c_double, y: c_double, w: c_double, h: c_double);
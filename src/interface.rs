@@ -17,14 +17,17 @@
 
 use std::ops::Index;
 use std::io::Write;
+use std::rc::Rc;
+use std::path::PathBuf;
 use std::vec;
 
 use picto::Region;
 use crate::error;
 use crate::config::Config;
 use crate::platform::{Clipboard, Key, Mouse};
-use crate::terminal::{Terminal, Mode, Iter, Cell};
-use crate::terminal::{cursor, touched};
+use crate::platform::key;
+use crate::terminal::{Terminal, Mode, Iter, Cell, Link, Size};
+use crate::terminal::{cursor, touched, graphics};
 use crate::overlay::Overlay;
 
 #[derive(Debug)]
@@ -38,10 +41,17 @@ pub enum Action {
 	Urgent,
 	Overlay(bool),
 	Title(String),
+	Icon(String),
 	Resize(u32, u32),
+	Iconify(bool),
+	Raise(bool),
+	Maximize(bool),
 	Copy(Clipboard, String),
 	Paste(Clipboard),
+	Query(Clipboard),
 	Open(Option<String>, String),
+	Save(Option<String>, Vec<u8>),
+	Spawn(Option<PathBuf>),
 }
 
 impl Interface {
@@ -84,6 +94,46 @@ impl Interface {
 		}
 	}
 
+	pub fn hover(&self) -> Option<&Rc<Link>> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.hover(),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.hover(),
+		}
+	}
+
+	pub fn directory(&self) -> Option<PathBuf> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.directory(),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.directory(),
+		}
+	}
+
+	pub fn refresh(&mut self) -> Option<String> {
+		match *self {
+			Interface::Terminal(ref mut terminal) =>
+				terminal.refresh(),
+
+			Interface::Overlay(ref mut overlay) =>
+				overlay.refresh(),
+		}
+	}
+
+	pub fn download(&mut self) -> Option<(Option<String>, Vec<u8>)> {
+		match *self {
+			Interface::Terminal(ref mut terminal) =>
+				terminal.download(),
+
+			Interface::Overlay(ref mut overlay) =>
+				overlay.download(),
+		}
+	}
+
 	pub fn columns(&self) -> u32 {
 		match *self {
 			Interface::Terminal(ref terminal) =>
@@ -124,6 +174,26 @@ impl Interface {
 		}
 	}
 
+	pub fn size(&self, y: u32) -> Size {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.size(y),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.size(y),
+		}
+	}
+
+	pub fn graphics(&self) -> Vec<graphics::Visible> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.visible(0, terminal.rows()),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.graphics(),
+		}
+	}
+
 	pub fn resize(&mut self, width: u32, height: u32) {
 		match *self {
 			Interface::Terminal(ref mut terminal) =>
@@ -168,9 +238,31 @@ impl Interface {
 		Ok(())
 	}
 
+	pub fn answer<O: Write>(&mut self, name: Clipboard, value: &[u8], output: O) -> error::Result<()> {
+		if let Interface::Terminal(ref mut terminal) = *self {
+			terminal.answer(name, value, output)?;
+		}
+
+		Ok(())
+	}
+
 	pub fn key<O: Write>(&mut self, key: Key, output: O) -> error::Result<(vec::IntoIter<Action>, touched::Iter)> {
+		// Bound keys swallow repeats and releases too, but only act on presses.
+		let press = key.kind() == key::Kind::Press;
+
 		if &key == self.config().input().prefix() {
-			return Ok((vec![Action::Overlay(!self.overlay())].into_iter(), touched::Iter::empty()));
+			let actions = if press { vec![Action::Overlay(!self.overlay())] } else { Vec::new() };
+			return Ok((actions.into_iter(), touched::Iter::empty()));
+		}
+
+		if Some(&key) == self.config().input().save() {
+			let actions = if press { self.download() } else { None }.map(|(name, content)| Action::Save(name, content));
+			return Ok((actions.into_iter().collect::<Vec<_>>().into_iter(), touched::Iter::empty()));
+		}
+
+		if Some(&key) == self.config().input().spawn() {
+			let actions = if press { vec![Action::Spawn(self.directory())] } else { Vec::new() };
+			return Ok((actions.into_iter(), touched::Iter::empty()));
 		}
 
 		match *self {
@@ -192,8 +284,8 @@ impl Interface {
 
 		match *self {
 			Interface::Terminal(ref mut terminal) => {
-				terminal.mouse(mouse, output)?;
-				Ok((Vec::new().into_iter(), touched::Iter::empty()))
+				let touched = terminal.mouse(mouse, output)?;
+				Ok((Vec::new().into_iter(), touched))
 			}
 
 			Interface::Overlay(ref mut overlay) => {
@@ -25,7 +25,7 @@
 #[cfg(feature = "fuzzy")]
 extern crate afl;
 
-#[macro_use(error, debug)]
+#[macro_use(error, debug, info)]
 extern crate log;
 extern crate env_logger;
 
@@ -97,6 +97,8 @@ fn main() {
 	use std::mem;
 	use std::io::Write;
 	use std::thread;
+	use std::env;
+	use std::process::Command;
 
 	use picto::Region;
 	use config::Config;
@@ -179,26 +181,41 @@ fn main() {
 
 		let mut renderer = Renderer::new(config.clone(), font.clone(), &surface, w, h);
 
-		let mut interface = Interface::from(Terminal::new(config.clone(),
-			(font.width(), font.height() + config.style().spacing()),
-			(renderer.columns(), renderer.rows()))?);
-
 		let mut tty = Tty::spawn(
 			matches.value_of("term").or_else(|| config.environment().term()),
 			matches.value_of("execute").or_else(|| config.environment().program()),
 			(font.width(), font.height() + config.style().spacing()),
 			(renderer.columns(), renderer.rows()))?;
 
+		let mut terminal = Terminal::new(config.clone(),
+			(font.width(), font.height() + config.style().spacing()),
+			(renderer.columns(), renderer.rows()),
+			Some(tty.id()))?;
+
+		let (width, height) = window.screen();
+		terminal.set_screen(width, height);
+
+		let mut interface = Interface::from(terminal);
+
 		let mut focused = true;
 		let mut visible = true;
 
 		let     blink    = timer::periodic_ms(config.style().blink());
 		let mut blinking = true;
 
+		// The title can depend on the directory and the foreground process, so
+		// it's checked at a slow pace instead of on every input.
+		let refresh = timer::periodic_ms(1000);
+
 		let (_batcher, mut batch) = channel();
 		let mut batching          = None;
 		let mut batched           = None;
 
+		let (_syncer, mut sync) = channel();
+		let mut syncing         = None;
+		let mut synced          = None;
+		let mut expired         = false;
+
 		let input = tty.output();
 
 		macro_rules! render {
@@ -227,7 +244,20 @@ fn main() {
 			(handle $what:expr) => ({
 				let (actions, touched) = r#try!(continue $what);
 
-				if touched.is_total() && batched.is_none() && config.environment().batch().is_some() {
+				// Hold back rendering while the application is updating, unless it
+				// took too long.
+				if interface.mode().contains(terminal::mode::SYNC) && !interface.overlay() {
+					if synced.is_none() && !expired {
+						syncing = Some(true);
+					}
+					else if expired && batched.is_none() && !touched.is_empty() {
+						render!(touched);
+					}
+				}
+				else if synced.is_some() {
+					syncing = Some(false);
+				}
+				else if touched.is_total() && batched.is_none() && config.environment().batch().is_some() {
 					batching = Some(true);
 				}
 				else if batched.is_none() && !touched.is_empty() {
@@ -254,15 +284,35 @@ fn main() {
 							window.set_title(string);
 						}
 
+						Action::Icon(string) => {
+							window.set_icon(string);
+						}
+
 						Action::Resize(columns, rows) => {
 							let (width, height) = Renderer::dimensions(columns, rows, &config, &font);
 							window.resize(width, height);
 						}
 
+						Action::Iconify(value) => {
+							window.iconify(value);
+						}
+
+						Action::Raise(value) => {
+							window.raise(value);
+						}
+
+						Action::Maximize(value) => {
+							window.maximize(value);
+						}
+
 						Action::Copy(name, value) => {
 							window.copy(name, value);
 						}
 
+						Action::Query(name) => {
+							window.query(name);
+						}
+
 						Action::Paste(name) => {
 							window.paste(name)
 						}
@@ -270,9 +320,45 @@ fn main() {
 						Action::Open(through, what) => {
 							window.open(through.as_ref().map(AsRef::as_ref), what.as_ref()).unwrap();
 						}
+
+						Action::Save(name, content) => {
+							match util::save(name.as_ref().map(AsRef::as_ref), &content) {
+								Ok(path) =>
+									info!(target: "cancer::runner", "saved file to {}", path.display()),
+
+								Err(error) =>
+									error!(target: "cancer::runner", "could not save file: {}", error),
+							}
+						}
+
+						Action::Spawn(directory) => {
+							if let Ok(path) = env::current_exe() {
+								let mut command = Command::new(path);
+								command.args(env::args_os().skip(1));
+
+								if let Some(directory) = directory {
+									command.current_dir(directory);
+								}
+
+								match command.spawn() {
+									// Reap the child once the window is closed.
+									Ok(mut child) => {
+										thread::spawn(move || child.wait());
+									}
+
+									Err(error) => {
+										error!(target: "cancer::runner", "could not spawn window: {}", error);
+									}
+								}
+							}
+						}
 					}
 				}
 
+				if !interface.mode().contains(terminal::mode::SYNC) {
+					expired = false;
+				}
+
 				r#try!(return tty.flush());
 			});
 
@@ -288,8 +374,28 @@ fn main() {
 
 		thread::Builder::new().name("cancer::runner".into()).spawn(async move || {
 			let _batcher = _batcher;
+			let _syncer  = _syncer;
 
 			loop {
+				match syncing.take() {
+					Some(true) => {
+						synced = Some(mem::replace(&mut sync,
+							timer::oneshot_ms(config.environment().synchronize())));
+					}
+
+					Some(false) => {
+						if let Some(empty) = synced.take() {
+							sync = empty;
+
+							if batched.is_none() {
+								render!(interface.region().absolute());
+							}
+						}
+					}
+
+					None => ()
+				}
+
 				match batching.take() {
 					Some(true) => {
 						batched = Some(mem::replace(&mut batch,
@@ -299,7 +405,10 @@ fn main() {
 					Some(false) => {
 						if let Some(empty) = batched.take() {
 							batch = empty;
-							render!(interface.region().absolute());
+
+							if synced.is_none() {
+								render!(interface.region().absolute());
+							}
 						}
 					}
 
@@ -311,15 +420,26 @@ fn main() {
 						batching = Some(false);
 					},
 
+					_ = sync.recv() => {
+						expired = true;
+						syncing = Some(false);
+					},
+
 					_ = blink.recv() => {
 						blinking = !blinking;
 
 						let blinked = interface.blinking(blinking);
-						if (!blinked.is_empty() || interface.cursor().blink()) && batched.is_none() {
+						if (!blinked.is_empty() || interface.cursor().blink()) && batched.is_none() && synced.is_none() {
 							render!(blinked);
 						}
 					},
 
+					_ = refresh.recv() => {
+						if let Some(title) = interface.refresh() {
+							window.set_title(title);
+						}
+					},
+
 					event = events.recv() => {
 						let event = r#try!(return event);
 						debug!(target: "cancer::runner", "{:?}", event);
@@ -381,6 +501,11 @@ fn main() {
 								r#try!(return tty.flush());
 							}
 
+							Event::Clipboard(name, value) => {
+								r#try!(return interface.answer(name, &value, tty.by_ref()));
+								r#try!(return tty.flush());
+							}
+
 							Event::Key(key) => {
 								render!(handle interface.key(key, tty.by_ref()));
 							}
@@ -390,8 +515,9 @@ fn main() {
 									Mouse::Click(mouse::Click { ref mut position, .. }) |
 									Mouse::Motion(mouse::Motion { ref mut position, .. }) => {
 										if let Some((x, y)) = renderer.position(position.x, position.y) {
-											position.x = x;
-											position.y = y;
+											position.pixels = renderer.pixels(position.x, position.y);
+											position.x      = x;
+											position.y      = y;
 										}
 										else {
 											continue;
@@ -438,7 +564,7 @@ fn main() {
 		.get_matches();
 
 	let mut terminal = Terminal::new(Arc::new(Config::default()),
-		(6, 11), (80, 24)).unwrap();
+		(6, 11), (80, 24), None).unwrap();
 
 	if matches.is_present("test") {
 		let mut content = Vec::new();
@@ -53,11 +53,13 @@ pub enum Move {
 pub enum Next {
 	Word(Word),
 	Match(Match),
+	Prompt,
 }
 
 pub enum Previous {
 	Word(Word),
 	Match(Match),
+	Prompt,
 }
 
 pub type Boundary = Box<dyn Fn(&str) -> bool>;
@@ -76,6 +78,7 @@ pub enum Select {
 	Normal,
 	Block,
 	Line,
+	Output,
 }
 
 pub enum Hint {
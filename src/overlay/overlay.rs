@@ -17,6 +17,7 @@
 
 use std::rc::Rc;
 use std::io::Write;
+use std::path::Path;
 use std::ops::{Index, Deref, DerefMut};
 use std::vec;
 use std::collections::HashMap;
@@ -30,10 +31,11 @@ use crate::style::{self, Style};
 use crate::platform::Clipboard;
 use crate::platform::key::{self, Key};
 use crate::platform::mouse::{self, Mouse};
-use crate::terminal::{Terminal, Cursor, Iter, Row};
+use crate::terminal::{Terminal, Cursor, Iter, Row, Mark, Size, Link};
 use crate::terminal::touched::{self, Touched};
 use crate::terminal::cell::{self, Cell};
 use crate::terminal::cursor;
+use crate::terminal::graphics;
 use crate::overlay::Status;
 use crate::overlay::command::{self, Command};
 use crate::overlay::hints::{Hint, Hints};
@@ -233,6 +235,28 @@ impl Overlay {
 		cursor::Cell::new(&self.cursor, cell::Position::new(x, y, &self[(x, y)]))
 	}
 
+	/// Get the line size of the given row.
+	pub fn size(&self, y: u32) -> Size {
+		if self.status.is_some() && y == self.inner.rows() - 1 {
+			return Size::Single;
+		}
+
+		let mut offset = (self.inner.rows() - 1 - y) + self.scroll;
+
+		if self.status.is_some() {
+			offset -= 1;
+		}
+
+		self[offset].size()
+	}
+
+	/// Get the images visible in the current view.
+	pub fn graphics(&self) -> Vec<graphics::Visible> {
+		let status = if self.status.is_some() { 1 } else { 0 };
+
+		self.inner.visible(self.scroll as i64 - status, self.inner.rows() - status as u32)
+	}
+
 	/// Get an iterator over positioned cells.
 	pub fn iter<T: Iterator<Item = (u32, u32)>>(&self, iter: T) -> Iter<Self, T> {
 		Iter::new(self, iter)
@@ -268,6 +292,10 @@ impl Overlay {
 
 		debug!(target: "cancer::overlay::input", "key {:?}", key);
 
+		if key.kind() == key::Kind::Release {
+			return (Vec::new().into_iter(), touched::Iter::empty());
+		}
+
 		// Check if the key is a number that makes operations run N times, if so
 		// bail out early.
 		//
@@ -374,6 +402,12 @@ impl Overlay {
 				"^" | "0" =>
 					Command::Move(command::Move::Start),
 
+				"{" =>
+					Command::Move(command::Move::Previous(times.unwrap_or(1), command::Previous::Prompt)),
+
+				"}" =>
+					Command::Move(command::Move::Next(times.unwrap_or(1), command::Next::Prompt)),
+
 				"h" if key.modifier().is_empty() =>
 					Command::Move(command::Move::Left(times.unwrap_or(1))),
 
@@ -408,6 +442,9 @@ impl Overlay {
 				"V" if key.modifier() == key::SHIFT =>
 					Command::Select(command::Select::Line),
 
+				"o" if key.modifier().is_empty() =>
+					Command::Select(command::Select::Output),
+
 				"y" if key.modifier().is_empty() =>
 					Command::Copy(match times {
 						Some(1) => Clipboard::Primary,
@@ -920,6 +957,44 @@ impl Overlay {
 				}
 			}
 
+			Command::Move(command::Move::Next(times, command::Next::Prompt)) => {
+				let (_, origin) = overlay!(self; cursor absolute);
+				let mut y       = origin;
+
+				for _ in 0 .. times {
+					if let Some(found) = self.marked(y, false, Mark::Prompt) {
+						y = found;
+					}
+					else {
+						break;
+					}
+				}
+
+				if y != origin {
+					self.command(Command::Move(command::Move::Down(origin - y)));
+					self.command(Command::Move(command::Move::Start));
+				}
+			}
+
+			Command::Move(command::Move::Previous(times, command::Previous::Prompt)) => {
+				let (_, origin) = overlay!(self; cursor absolute);
+				let mut y       = origin;
+
+				for _ in 0 .. times {
+					if let Some(found) = self.marked(y, true, Mark::Prompt) {
+						y = found;
+					}
+					else {
+						break;
+					}
+				}
+
+				if y != origin {
+					self.command(Command::Move(command::Move::Up(y - origin)));
+					self.command(Command::Move(command::Move::Start));
+				}
+			}
+
 			// Selection commands.
 			Command::Select(mode) => {
 				let (name, old, new) = match (mode, self.selector.current.take()) {
@@ -1004,6 +1079,20 @@ impl Overlay {
 							None,
 							Some(Selection::Line { start: y, end: y }))
 					}
+
+					(command::Select::Output, old) => {
+						let (_, y) = overlay!(self; cursor absolute);
+
+						if let Some((start, end)) = self.output(y) {
+							("VISUAL LINE",
+								old,
+								Some(Selection::Line { start: start, end: end }))
+						}
+						else {
+							self.selector.current = old;
+							return actions;
+						}
+					}
 				};
 
 				overlay!(self; status mode name);
@@ -1042,24 +1131,32 @@ impl Overlay {
 				let config  = self.inner.config().overlay().hinter(id).clone();
 				let content = self.selection(&Selection::Line { start: top, end: bottom });
 				let urls    = config.matcher().find_iter(&content).collect::<Vec<_>>();
+				let links   = self.links(top, bottom);
 
-				if !urls.is_empty() {
+				if !urls.is_empty() || !links.is_empty() {
 					overlay!(self; status mode "HINT");
 
 					self.hinter.label  = Rc::new(*config.style());
 					self.hinter.hinted = Rc::new(Style {
 						foreground: config.style().foreground,
 						background: config.style().background,
+						underline:  config.style().underline,
 						attributes: config.style().attributes ^ style::REVERSE,
+						palette:    config.style().palette,
 					});
 
-					self.hinter.hints  = Some(Hints::new(config.label().to_vec(), urls.len()));
+					self.hinter.hints  = Some(Hints::new(config.label().to_vec(), urls.len() + links.len()));
 					self.hinter.config = config;
 
 					for url in urls {
 						self.hint((url.start(), url.end()), &content);
 					}
 
+					for (position, uri) in links {
+						let hint = self.hinter.hints.as_mut().unwrap().put(position, uri).clone();
+						self.highlight(Highlight::Hint(&hint, 0), true);
+					}
+
 					self.touched.all();
 				}
 				else {
@@ -1115,7 +1212,7 @@ impl Overlay {
 				actions.push(Action::Overlay(false));
 
 				if let Some(hint) = self.hinter.get() {
-					actions.push(Action::Open(self.hinter.config.opener().map(String::from), hint.into()));
+					actions.push(Action::Open(self.hinter.config.opener().map(String::from), self.resolve(hint)));
 				}
 			}
 
@@ -1360,6 +1457,105 @@ impl Overlay {
 		}
 	}
 
+	/// Resolve a relative path against the working directory of the program.
+	fn resolve(&self, hint: &str) -> String {
+		if hint.contains("://") || Path::new(hint).is_absolute() {
+			return hint.into();
+		}
+
+		if let Some(path) = self.inner.directory().map(|d| d.join(hint)) {
+			if path.exists() {
+				return path.to_string_lossy().into_owned();
+			}
+		}
+
+		hint.into()
+	}
+
+	/// Find the closest row above or below the given one with the given mark.
+	fn marked(&self, y: u32, up: bool, mark: Mark) -> Option<u32> {
+		let rows = (self.inner.grid().back().len() + self.inner.grid().view().len()) as u32;
+
+		if up {
+			(y + 1 .. rows).find(|&y| self[y].is_marked(mark))
+		}
+		else {
+			(0 .. y).rev().find(|&y| self[y].is_marked(mark))
+		}
+	}
+
+	/// Find the rows containing the output of the command at the given row.
+	fn output(&self, y: u32) -> Option<(u32, u32)> {
+		let rows  = (self.inner.grid().back().len() + self.inner.grid().view().len()) as u32;
+		let start = (y .. rows).find(|&y| self[y].is_marked(Mark::Output) || self[y].is_marked(Mark::End(None)))?;
+
+		if !self[start].is_marked(Mark::Output) {
+			return None;
+		}
+
+		// The output mark may share the row with the command line.
+		let start = if self[start].is_marked(Mark::Prompt) || self[start].is_marked(Mark::Command) {
+			start.checked_sub(1)?
+		}
+		else {
+			start
+		};
+
+		let end = (0 .. start + 1).rev()
+			.find(|&y| self[y].is_marked(Mark::End(None)) || self[y].is_marked(Mark::Prompt))
+			.map(|y| y + 1)
+			.unwrap_or(0);
+
+		if end > start || end > y {
+			return None;
+		}
+
+		Some((start, end))
+	}
+
+	/// Find the explicit hyperlinks within the given rows.
+	fn links(&self, top: u32, bottom: u32) -> Vec<(((u32, u32), (u32, u32)), String)> {
+		let mut links   = Vec::new();
+		let mut current = None::<(Rc<Link>, (u32, u32), (u32, u32))>;
+
+		for y in (bottom ..= top).rev() {
+			for x in 0 .. self.inner.columns() {
+				let cell = &self[y][x as usize];
+
+				if cell.is_reference() {
+					continue;
+				}
+
+				let end = if x + cell.width() >= self.inner.columns() {
+					(0, y.wrapping_sub(1))
+				}
+				else {
+					(x + cell.width(), y)
+				};
+
+				match (current.take(), cell.link()) {
+					(Some((link, start, _)), Some(next)) if Rc::ptr_eq(&link, next) => {
+						current = Some((link, start, end));
+					}
+
+					(previous, next) => {
+						if let Some((link, start, end)) = previous {
+							links.push(((start, end), link.uri().into()));
+						}
+
+						current = next.map(|link| (link.clone(), (x, y), end));
+					}
+				}
+			}
+		}
+
+		if let Some((link, start, end)) = current {
+			links.push(((start, end), link.uri().into()));
+		}
+
+		links
+	}
+
 	/// Mark cells in a terminal as a hint.
 	fn hint<T: AsRef<str>>(&mut self, (start, end): (usize, usize), content: T) {
 		let content = content.as_ref();
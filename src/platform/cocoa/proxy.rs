@@ -22,9 +22,9 @@ use std::ffi::CStr;
 use std::str;
 use std::sync::mpsc::Sender;
 
-use cocoa::foundation::{NSString, NSSize, NSArray};
+use cocoa::foundation::{NSString, NSSize, NSRect, NSArray};
 use cocoa::appkit::{NSWindow, NSView, NSSound, NSPasteboard, NSPasteboardTypeString};
-use cocoa::base::{class, nil};
+use cocoa::base::{class, id, nil};
 
 use crate::sys::cairo;
 use crate::error;
@@ -56,6 +56,21 @@ impl platform::Proxy for Proxy {
 		}
 	}
 
+	fn screen(&self) -> (u32, u32) {
+		unsafe {
+			let screen: id = msg_send![*self.window, screen];
+
+			if screen == nil {
+				return self.dimensions();
+			}
+
+			let frame: NSRect = msg_send![screen, visibleFrame];
+			let factor        = NSWindow::backingScaleFactor(*self.window) as f32;
+
+			((factor * frame.size.width as f32) as u32, (factor * frame.size.height as f32) as u32)
+		}
+	}
+
 	fn surface(&self) -> error::Result<cairo::Surface> {
 		unsafe {
 			let (width, height) = self.dimensions();
@@ -73,7 +88,20 @@ impl platform::Proxy for Proxy {
 
 	fn resize(&mut self, width: u32, height: u32) {
 		unsafe {
-			self.window.setContentSize_(NSSize::new(width as f64, height as f64));
+			let mut width  = width as f64;
+			let mut height = height as f64;
+
+			// Never grow beyond the screen.
+			let screen: id = msg_send![*self.window, screen];
+
+			if screen != nil {
+				let frame: NSRect = msg_send![screen, visibleFrame];
+
+				width  = width.min(frame.size.width);
+				height = height.min(frame.size.height);
+			}
+
+			self.window.setContentSize_(NSSize::new(width, height));
 		}
 	}
 
@@ -83,6 +111,44 @@ impl platform::Proxy for Proxy {
 		}
 	}
 
+	fn set_icon(&self, name: String) {
+		unsafe {
+			let _: () = msg_send![*self.window, setMiniwindowTitle:*IdRef::new(NSString::alloc(nil).init_str(&name))];
+		}
+	}
+
+	fn iconify(&self, value: bool) {
+		unsafe {
+			if value {
+				let _: () = msg_send![*self.window, miniaturize:nil];
+			}
+			else {
+				let _: () = msg_send![*self.window, deminiaturize:nil];
+			}
+		}
+	}
+
+	fn raise(&self, value: bool) {
+		unsafe {
+			if value {
+				let _: () = msg_send![*self.window, orderFront:nil];
+			}
+			else {
+				let _: () = msg_send![*self.window, orderBack:nil];
+			}
+		}
+	}
+
+	fn maximize(&self, value: bool) {
+		unsafe {
+			let zoomed: bool = msg_send![*self.window, isZoomed];
+
+			if zoomed != value {
+				let _: () = msg_send![*self.window, zoom:nil];
+			}
+		}
+	}
+
 	fn copy(&self, _name: Clipboard, value: String) {
 		unsafe {
 			let paste = NSPasteboard::generalPasteboard(nil);
@@ -107,6 +173,23 @@ impl platform::Proxy for Proxy {
 		}
 	}
 
+	fn query(&self, name: Clipboard) {
+		unsafe {
+			if let Some(manager) = self.manager.as_ref() {
+				let paste = NSPasteboard::generalPasteboard(nil);
+				let value = paste.stringForType(NSPasteboardTypeString);
+				let value = if value != nil {
+					CStr::from_ptr(value.UTF8String()).to_bytes().to_vec()
+				}
+				else {
+					Vec::new()
+				};
+
+				let _ = manager.send(Event::Clipboard(name, value));
+			}
+		}
+	}
+
 	fn urgent(&self) {
 		unsafe {
 			if let Some(sound) = self.config.environment().cocoa().bell() {
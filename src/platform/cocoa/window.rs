@@ -107,8 +107,9 @@ impl Window {
 			let factor = window.backingScaleFactor() as f32;
 
 			mouse::Position {
-				x: (factor * point.x as f32) as u32,
-				y: (factor * (rect.size.height - point.y) as f32) as u32,
+				x:      (factor * point.x as f32) as u32,
+				y:      (factor * (rect.size.height - point.y) as f32) as u32,
+				pixels: (0, 0),
 			}
 		}
 
@@ -16,7 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use picto::Region;
-use crate::platform::{Key, Mouse};
+use crate::platform::{Key, Mouse, Clipboard};
 
 #[allow(dead_code)]
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -42,6 +42,9 @@ pub enum Event {
 	/// Paste request.
 	Paste(Vec<u8>),
 
+	/// Clipboard contents requested through a query.
+	Clipboard(Clipboard, Vec<u8>),
+
 	/// Key press.
 	Key(Key),
 
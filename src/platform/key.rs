@@ -20,15 +20,32 @@ pub struct Key {
 	value:    Value,
 	modifier: Modifier,
 	lock:     Lock,
+	kind:     Kind,
+	base:     Option<char>,
 }
 
-/// Implementation to ignore locks, they're just informational.
+/// Implementation to ignore locks, the kind of event and the base key, they're
+/// just informational.
 impl PartialEq for Key {
 	fn eq(&self, other: &Key) -> bool {
 		self.modifier == other.modifier && self.value == other.value
 	}
 }
 
+/// Whether the key was pressed, is repeating or was released.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Kind {
+	Press,
+	Repeat,
+	Release,
+}
+
+impl Default for Kind {
+	fn default() -> Self {
+		Kind::Press
+	}
+}
+
 bitflags! {
 	pub struct Modifier: u8 {
 		const ALT   = 1 << 0;
@@ -146,6 +163,8 @@ impl Key {
 			value:    value,
 			modifier: modifier,
 			lock:     lock,
+			kind:     Kind::default(),
+			base:     None,
 		}
 	}
 
@@ -163,4 +182,24 @@ impl Key {
 	pub fn lock(&self) -> Lock {
 		self.lock
 	}
+
+	/// Get the kind of event.
+	pub fn kind(&self) -> Kind {
+		self.kind
+	}
+
+	/// Change the kind of event.
+	pub fn set_kind(&mut self, kind: Kind) {
+		self.kind = kind;
+	}
+
+	/// Get the character the key produces without any modifiers, if known.
+	pub fn base(&self) -> Option<char> {
+		self.base
+	}
+
+	/// Change the character the key produces without any modifiers.
+	pub fn set_base(&mut self, base: Option<char>) {
+		self.base = base;
+	}
 }
@@ -50,4 +50,8 @@ pub enum Button {
 pub struct Position {
 	pub x: u32,
 	pub y: u32,
+
+	/// The position in pixels from the first cell, filled in when `x` and `y`
+	/// are turned into cells.
+	pub pixels: (u32, u32),
 }
@@ -26,6 +26,11 @@ pub trait Proxy: Send {
 	/// Get the Window dimensions.
 	fn dimensions(&self) -> (u32, u32);
 
+	/// Get the dimensions of the screen the Window is on.
+	fn screen(&self) -> (u32, u32) {
+		self.dimensions()
+	}
+
 	/// Create a Cairo surface.
 	fn surface(&self) -> error::Result<cairo::Surface>;
 
@@ -38,12 +43,27 @@ pub trait Proxy: Send {
 	/// Set the window title.
 	fn set_title(&self, title: String) { }
 
+	/// Set the window icon name.
+	fn set_icon(&self, name: String) { }
+
+	/// Iconify or restore the window.
+	fn iconify(&self, value: bool) { }
+
+	/// Raise or lower the window.
+	fn raise(&self, value: bool) { }
+
+	/// Maximize or restore the window.
+	fn maximize(&self, value: bool) { }
+
 	/// Change the clipboard contents.
 	fn copy(&self, name: Clipboard, value: String) { }
 
 	/// Request the clipboard contents.
 	fn paste(&self, name: Clipboard) { }
 
+	/// Request the clipboard contents to answer a query.
+	fn query(&self, name: Clipboard) { }
+
 	/// Ask senpai to notice you.
 	fn urgent(&self) { }
 
@@ -170,6 +170,11 @@ impl Tty {
 		(i_sender, o_receiver)
 	}
 
+	/// Get the process ID of the spawned program.
+	pub fn id(&self) -> i32 {
+		self.id as i32
+	}
+
 	pub fn output(&mut self) -> Receiver<Vec<u8>> {
 		self.output.take().unwrap()
 	}
@@ -23,7 +23,7 @@ use xcbu::ewmh;
 use xkb;
 
 use crate::error;
-use crate::platform::key::{self, Key, Button, Keypad, Modifier, Lock};
+use crate::platform::key::{self, Key, Value, Button, Keypad, Modifier, Lock};
 
 pub struct Keyboard {
 	connection: Arc<ewmh::Connection>,
@@ -35,6 +35,7 @@ pub struct Keyboard {
 	#[allow(dead_code)]
 	table:   xkb::compose::Table,
 	compose: xkb::compose::State,
+	pressed: Option<u8>,
 }
 
 unsafe impl Send for Keyboard { }
@@ -79,6 +80,18 @@ impl Keyboard {
 				map as u16, map as u16, None).request_check()?;
 		}
 
+		// Don't send a synthetic release before every repeated press, so
+		// repeats can be told apart.
+		{
+			let flag  = xcb::xkb::PER_CLIENT_FLAG_DETECTABLE_AUTO_REPEAT;
+			let reply = xcb::xkb::per_client_flags(&connection,
+				xcb::xkb::ID_USE_CORE_KBD as u16, flag, flag, 0, 0, 0).get_reply()?;
+
+			if reply.value() & flag == 0 {
+				debug!(target: "cancer::platform::key", "detectable auto-repeat not supported");
+			}
+		}
+
 		let context = xkb::Context::default();
 		let device  = xkb::x11::device(&connection)?;
 		let keymap  = xkb::x11::keymap(&connection, device, &context, Default::default())?;
@@ -107,6 +120,7 @@ impl Keyboard {
 
 			table:   table,
 			compose: compose,
+			pressed: None,
 		})
 	}
 
@@ -156,6 +170,72 @@ impl Keyboard {
 	}
 
 	pub fn key(&mut self, code: u8) -> Option<Key> {
+		let (modifier, lock) = self.modifiers();
+		let symbol           = r#try!(option self.symbol(code));
+		self.compose.feed(symbol);
+
+		debug!(target: "cancer::platform::key", "compose status: {:?}", self.compose.status());
+
+		match self.compose.status() {
+			xkb::compose::Status::Nothing => (),
+			xkb::compose::Status::Composing =>
+				return None,
+
+			xkb::compose::Status::Composed => {
+				if let Some(string) = self.compose.utf8() {
+					self.compose.reset();
+					self.pressed = Some(code);
+
+					return Some(Key::new(string.into(), modifier, lock));
+				}
+			}
+
+			xkb::compose::Status::Cancelled => {
+				self.compose.reset();
+				return None;
+			}
+		}
+
+		let mut key = Key::new(r#try!(option self.value(code, symbol, modifier)), modifier, lock);
+		key.set_base(self.base(code));
+
+		// A press without a release in between is the key repeating.
+		if self.pressed == Some(code) {
+			key.set_kind(key::Kind::Repeat);
+		}
+
+		self.pressed = Some(code);
+		Some(key)
+	}
+
+	/// Get the key for a released key code.
+	pub fn release(&mut self, code: u8) -> Option<Key> {
+		let (modifier, lock) = self.modifiers();
+		let symbol           = r#try!(option self.symbol(code));
+		let mut key          = Key::new(r#try!(option self.value(code, symbol, modifier)), modifier, lock);
+		key.set_base(self.base(code));
+
+		if self.pressed == Some(code) {
+			self.pressed = None;
+		}
+
+		key.set_kind(key::Kind::Release);
+		Some(key)
+	}
+
+	/// Get the character of the first level of the key in the active layout,
+	/// which is what the keyboard protocol reports as the key code.
+	fn base(&self, code: u8) -> Option<char> {
+		let layout = self.state.key(code).layout()?;
+
+		self.keymap.key(xkb::Keycode(code as u32)).syms(xkb::LayoutIndex(layout as u32), xkb::LevelIndex(0))
+			.first()
+			.and_then(|symbol| char::from_u32(symbol.utf32()))
+			.filter(|&ch| ch != '\0')
+	}
+
+	/// Get the currently active modifiers and locks.
+	fn modifiers(&self) -> (Modifier, Lock) {
 		let modifier = [
 			(xkb::name::mods::ALT,   key::ALT),
 			(xkb::name::mods::CTRL,  key::CTRL),
@@ -180,30 +260,12 @@ impl Keyboard {
 				lock
 			});
 
-		let symbol = r#try!(option self.symbol(code));
-		self.compose.feed(symbol);
-
-		debug!(target: "cancer::platform::key", "compose status: {:?}", self.compose.status());
-
-		match self.compose.status() {
-			xkb::compose::Status::Nothing => (),
-			xkb::compose::Status::Composing =>
-				return None,
-
-			xkb::compose::Status::Composed => {
-				if let Some(string) = self.compose.utf8() {
-					self.compose.reset();
-					return Some(Key::new(string.into(), modifier, lock));
-				}
-			}
-
-			xkb::compose::Status::Cancelled => {
-				self.compose.reset();
-				return None;
-			}
-		}
+		(modifier, lock)
+	}
 
-		Some(Key::new(match symbol {
+	/// Translate a key symbol to the key value.
+	fn value(&self, code: u8, symbol: xkb::Keysym, modifier: Modifier) -> Option<Value> {
+		Some(match symbol {
 			xkb::key::Tab | xkb::key::ISO_Left_Tab =>
 				Button::Tab.into(),
 
@@ -363,6 +425,6 @@ impl Keyboard {
 
 				string.into()
 			}
-		}, modifier, lock))
+		})
 	}
 }
@@ -16,11 +16,12 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::cmp;
 use std::sync::mpsc::Sender;
 use std::process::Command;
 
 use xcb;
-use xcbu::ewmh;
+use xcbu::{icccm, ewmh};
 
 use crate::sys::cairo;
 use crate::error;
@@ -43,6 +44,11 @@ impl platform::Proxy for Proxy {
 		(reply.width() as u32, reply.height() as u32)
 	}
 
+	fn screen(&self) -> (u32, u32) {
+		let screen = self.connection.get_setup().roots().nth(self.screen as usize).unwrap();
+		(screen.width_in_pixels() as u32, screen.height_in_pixels() as u32)
+	}
+
 	fn surface(&self) -> error::Result<cairo::Surface> {
 		let screen          = self.connection.get_setup().roots().nth(self.screen as usize).unwrap();
 		let (width, height) = self.dimensions();
@@ -59,6 +65,11 @@ impl platform::Proxy for Proxy {
 	}
 
 	fn resize(&mut self, width: u32, height: u32) {
+		// Never grow beyond the screen.
+		let limit  = self.screen();
+		let width  = cmp::min(width, limit.0);
+		let height = cmp::min(height, limit.1);
+
 		self.request.send(Request::Resize(width, height)).unwrap();
 	}
 
@@ -66,6 +77,43 @@ impl platform::Proxy for Proxy {
 		self.request.send(Request::Title(title)).unwrap();
 	}
 
+	fn set_icon(&self, name: String) {
+		self.request.send(Request::Icon(name)).unwrap();
+	}
+
+	fn iconify(&self, value: bool) {
+		if value {
+			let root  = self.connection.get_setup().roots().nth(self.screen as usize).unwrap().root();
+			let state = xcb::intern_atom(&self.connection, false, "WM_CHANGE_STATE").get_reply().unwrap().atom();
+			let event = xcb::ClientMessageEvent::new(32, self.window, state,
+				xcb::ClientMessageData::from_data32([icccm::WM_STATE_ICONIC as u32, 0, 0, 0, 0]));
+
+			xcb::send_event(&self.connection, false, root,
+				xcb::EVENT_MASK_SUBSTRUCTURE_REDIRECT | xcb::EVENT_MASK_SUBSTRUCTURE_NOTIFY, &event);
+		}
+		else {
+			xcb::map_window(&self.connection, self.window);
+		}
+
+		self.connection.flush();
+	}
+
+	fn raise(&self, value: bool) {
+		xcb::configure_window(&self.connection, self.window, &[
+			(xcb::CONFIG_WINDOW_STACK_MODE as u16, if value { xcb::STACK_MODE_ABOVE } else { xcb::STACK_MODE_BELOW })]);
+
+		self.connection.flush();
+	}
+
+	fn maximize(&self, value: bool) {
+		ewmh::request_change_wm_state(&self.connection, self.screen, self.window,
+			if value { ewmh::STATE_ADD } else { ewmh::STATE_REMOVE },
+			self.connection.WM_STATE_MAXIMIZED_VERT(), self.connection.WM_STATE_MAXIMIZED_HORZ(),
+			ewmh::CLIENT_SOURCE_TYPE_NORMAL);
+
+		self.connection.flush();
+	}
+
 	fn copy(&self, name: Clipboard, value: String) {
 		self.request.send(Request::Copy(name, value)).unwrap();
 	}
@@ -74,6 +122,10 @@ impl platform::Proxy for Proxy {
 		self.request.send(Request::Paste(name)).unwrap();
 	}
 
+	fn query(&self, name: Clipboard) {
+		self.request.send(Request::Query(name)).unwrap();
+	}
+
 	fn urgent(&self) {
 		self.request.send(Request::Urgent).unwrap();
 	}
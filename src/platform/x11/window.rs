@@ -49,9 +49,11 @@ pub enum Request {
 	Flush,
 	Urgent,
 	Title(String),
+	Icon(String),
 	Resize(u32, u32),
 	Copy(Clipboard, String),
 	Paste(Clipboard),
+	Query(Clipboard),
 }
 
 impl Window {
@@ -107,6 +109,10 @@ impl Window {
 			icccm::set_wm_class(&connection, window, name.unwrap_or("cancer"), "Terminal");
 			icccm::set_wm_name(&connection, window, name.unwrap_or("cancer"));
 			ewmh::set_wm_name(&connection, window, name.unwrap_or("cancer"));
+			// The icon name isn't Latin-1, so it's marked as UTF-8.
+			let utf8 = xcb::intern_atom(&connection, false, "UTF8_STRING").get_reply()?.atom();
+			icccm::set_wm_icon_name(&connection, window, utf8, 8, name.unwrap_or("cancer"));
+			ewmh::set_wm_icon_name(&connection, window, name.unwrap_or("cancer"));
 
 			icccm::set_wm_size_hints(&connection, window, xcb::ATOM_WM_NORMAL_HINTS, &icccm::SizeHints::empty()
 				.base((margin * 2) as i32, (margin * 2) as i32)
@@ -158,6 +164,7 @@ impl Window {
 		}
 
 		let mut clipboard = HashMap::new();
+		let mut query     = None;
 		let     requests  = self.receiver.take().unwrap();
 		let     events    = sink(self.connection.clone());
 
@@ -168,6 +175,7 @@ impl Window {
 		let STRING      = xcb::ATOM_STRING;
 		let TARGETS     = xcb::intern_atom(&self.connection, false, "TARGETS").get_reply().unwrap().atom();
 		let SELECTION   = xcb::intern_atom(&self.connection, false, "CANCER_CLIPBOARD").get_reply().unwrap().atom();
+		let QUERY       = xcb::intern_atom(&self.connection, false, "CANCER_QUERY").get_reply().unwrap().atom();
 
 		loop {
 			select! {
@@ -189,6 +197,11 @@ impl Window {
 							ewmh::set_wm_name(&self.connection, self.window, title);
 						}
 
+						Request::Icon(ref name) => {
+							icccm::set_wm_icon_name(&self.connection, self.window, UTF8_STRING, 8, name);
+							ewmh::set_wm_icon_name(&self.connection, self.window, name);
+						}
+
 						Request::Resize(w, h) => {
 							xcb::configure_window(&self.connection, self.window, &[
 								(xcb::CONFIG_WINDOW_WIDTH as u16, w),
@@ -221,6 +234,20 @@ impl Window {
 							xcb::convert_selection(&self.connection, self.window, atom, UTF8_STRING, SELECTION, xcb::CURRENT_TIME);
 							self.connection.flush();
 						}
+
+						Request::Query(name) => {
+							let atom = match name {
+								Clipboard::Primary   => PRIMARY,
+								Clipboard::Secondary => SECONDARY,
+								Clipboard::System    => CLIPBOARD,
+							};
+
+							debug!(target: "cancer::platform::clipboard", "query clipboard: {:?}({:?})", name, atom);
+
+							query = Some(name);
+							xcb::convert_selection(&self.connection, self.window, atom, UTF8_STRING, QUERY, xcb::CURRENT_TIME);
+							self.connection.flush();
+						}
 					}
 				},
 
@@ -320,6 +347,14 @@ impl Window {
 									manager.send(Event::Paste(reply.name().as_bytes().to_vec()))?;
 								}
 							}
+							else if event.atom() == QUERY && event.state() == xcb::PROPERTY_NEW_VALUE as u8 {
+								let reply = r#try!(continue icccm::get_text_property(&self.connection, self.window, QUERY).get_reply());
+								xcb::delete_property(&self.connection, self.window, QUERY);
+
+								if let Some(name) = query.take() {
+									manager.send(Event::Clipboard(name, reply.name().as_bytes().to_vec()))?;
+								}
+							}
 						}
 
 						xcb::BUTTON_PRESS | xcb::BUTTON_RELEASE => {
@@ -344,8 +379,9 @@ impl Window {
 								button:   button,
 								modifier: key::Modifier::from(event.state()),
 								position: mouse::Position {
-									x: event.event_x() as u32,
-									y: event.event_y() as u32,
+									x:      event.event_x() as u32,
+									y:      event.event_y() as u32,
+									pixels: (0, 0),
 								}
 							})))?;
 						}
@@ -356,8 +392,9 @@ impl Window {
 							manager.send(Event::Mouse(Mouse::Motion(mouse::Motion {
 								modifier: key::Modifier::from(event.state()),
 								position: mouse::Position {
-									x: event.event_x() as u32,
-									y: event.event_y() as u32,
+									x:      event.event_x() as u32,
+									y:      event.event_y() as u32,
+									pixels: (0, 0),
 								}
 							})))?;
 						}
@@ -374,6 +411,14 @@ impl Window {
 							}
 						}
 
+						xcb::KEY_RELEASE => {
+							let event = unsafe { xcb::cast_event::<xcb::KeyReleaseEvent>(&event) };
+
+							if let Some(key) = self.keyboard.release(event.detail()) {
+								manager.send(Event::Key(key))?;
+							}
+						}
+
 						e => {
 							debug!(target: "cancer::platform", "unhandled X event: {:?}", e);
 						}
@@ -43,8 +43,8 @@ impl Renderer {
 		let margin  = config.style().margin();
 		let spacing = config.style().spacing();
 
-		let width  = (columns * font.width()) + (margin * 2);
-		let height = (rows * (font.height() + spacing)) + (margin * 2);
+		let width  = columns.saturating_mul(font.width()).saturating_add(margin * 2);
+		let height = rows.saturating_mul(font.height() + spacing).saturating_add(margin * 2);
 
 		(width, height)
 	}
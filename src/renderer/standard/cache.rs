@@ -18,7 +18,7 @@
 use std::rc::Rc;
 
 use crate::style::{self, Style};
-use crate::terminal::cell;
+use crate::terminal::{cell, Size};
 use crate::renderer::Options;
 
 /// Cache for cells to avoid rendering a cell multiple times when it's not
@@ -35,6 +35,7 @@ pub struct Cache {
 pub struct Cell {
 	style: Rc<Style>,
 	value: Option<String>,
+	size:  Size,
 	flags: Flags,
 }
 
@@ -44,6 +45,7 @@ impl Cell {
 		Cell {
 			style: style,
 			value: None,
+			size:  Size::Single,
 			flags: Flags::empty(),
 		}
 	}
@@ -55,6 +57,8 @@ bitflags! {
 		const VALID    = 1 << 0;
 		const BLINKING = 1 << 1;
 		const REVERSE  = 1 << 2;
+		const HOVER    = 1 << 3;
+		const IMAGE    = 1 << 4;
 	}
 }
 
@@ -99,8 +103,8 @@ impl Cache {
 	/// Update the cache, returns `false` if the cache is valid.
 	///
 	/// The cell is seen as unchanged if it's valid, the style and content match
-	/// and the rendering options match.
-	pub fn update(&mut self, cell: &cell::Position, options: Options) -> bool {
+	/// and the rendering options match, cells with images are always redrawn.
+	pub fn update(&mut self, cell: &cell::Position, size: Size, options: Options, hover: bool, image: bool) -> bool {
 		debug_assert!(!cell.is_reference());
 
 		let index = (cell.y() * self.width + cell.x()) as usize;
@@ -109,10 +113,13 @@ impl Cache {
 		{
 			let cache = &self.inner[index];
 
-			if cache.flags.contains(VALID) &&
+			if !image && !cache.flags.contains(IMAGE) &&
+			   cache.flags.contains(VALID) &&
 			   cache.flags.contains(REVERSE) == options.reverse() &&
+			   cache.flags.contains(HOVER) == hover &&
 			   (!cache.style.attributes().contains(style::BLINK) ||
 				   cache.flags.contains(BLINKING) == options.blinking()) &&
+			   cache.size == size &&
 			   cell.style() == &cache.style &&
 			   ((cell.is_empty() && cache.value.is_none()) ||
 			    (cell.is_occupied() && cache.value.as_ref().map(AsRef::as_ref) == Some(cell.value())))
@@ -125,9 +132,12 @@ impl Cache {
 		self.inner[index] = Cell {
 			style: cell.style().clone(),
 			value: if cell.is_empty() { None } else { Some(cell.value().into()) },
+			size:  size,
 			flags: VALID
 				| if options.blinking() { BLINKING } else { NONE }
 				| if options.reverse() { REVERSE } else { NONE }
+				| if hover { HOVER } else { NONE }
+				| if image { IMAGE } else { NONE }
 		};
 
 		// Invalidate reference cells.
@@ -16,15 +16,18 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::mem;
+use std::cmp;
 use std::rc::Rc;
 
 use picto::Region;
+use picto::color::Rgba;
+use crate::config::{self, Config};
 use crate::config::style::Shape;
 use crate::sys::cairo;
 use crate::style;
-use crate::terminal::{cell, cursor};
+use crate::terminal::{cell, cursor, graphics, Link, Size};
 use crate::interface::Interface;
-use crate::renderer::{State, Options};
+use crate::renderer::{State, Options, option};
 use crate::renderer::standard::{Cache, Glyphs};
 
 /// Renderer for a `cairo::Surface`.
@@ -32,6 +35,8 @@ pub struct Renderer {
 	context: cairo::Context,
 	cache:   Cache,
 	glyphs:  Glyphs,
+	colors:  (Rgba<f64>, Rgba<f64>),
+	palette: config::Color,
 }
 
 unsafe impl Send for Renderer { }
@@ -42,11 +47,15 @@ impl Renderer {
 		let context = cairo::Context::new(surface);
 		let cache   = Cache::new(state.columns(), state.rows());
 		let glyphs  = Glyphs::new(state.config().environment().cache(), state.font().clone());
+		let colors  = (*state.config().style().color().foreground(), *state.config().style().color().background());
+		let palette = state.config().color().clone();
 
 		Renderer {
 			context: context,
 			cache:   cache,
 			glyphs:  glyphs,
+			colors:  colors,
+			palette: palette,
 		}
 	}
 
@@ -57,24 +66,42 @@ impl Renderer {
 	}
 
 	/// Render the given changes.
-	pub fn render<I>(&mut self, state: &State, options: Options, region: Option<Region>, interface: &Interface, iter: I)
+	pub fn render<I>(&mut self, state: &State, mut options: Options, mut region: Option<Region>, interface: &Interface, iter: I)
 		where I: Iterator<Item = (u32, u32)>
 	{
+		// The default colors and the palette can be changed by the terminal, in
+		// which case the cache is useless and the margins have to be repainted.
+		let config = interface.config();
+		let colors = (*config.style().color().foreground(), *config.style().color().background());
+
+		if self.colors != colors || &self.palette != config.color() {
+			self.colors  = colors;
+			self.palette = config.color().clone();
+			options.insert(option::DAMAGE);
+			region = region.or_else(|| Some(Region::from(0, 0, state.width(), state.height())));
+		}
+
 		self.context.push();
 
 		if let Some(region) = region {
-			self.margin(state, &region);
+			self.margin(state, config, &region);
 		}
 
+		let hover    = interface.hover();
+		let graphics = interface.graphics();
+
 		for cell in interface.iter(iter) {
-			self.cell(state, &cell, options);
+			let size = interface.size(cell.y());
+			self.cell(state, config, &cell, size, hover, &graphics, options);
 		}
 
 		if options.cursor() {
-			self.cursor(state, &interface.cursor(), options);
+			let cursor = interface.cursor();
+			self.cursor(state, config, &cursor, interface.size(cursor.cell().y()), options);
 		}
 		else {
-			self.cell(state, &interface.cursor().cell(), options);
+			let cursor = interface.cursor();
+			self.cell(state, config, &cursor.cell(), interface.size(cursor.cell().y()), hover, &graphics, options);
 		}
 
 		self.context.pop();
@@ -82,9 +109,9 @@ impl Renderer {
 	}
 
 	/// Draw the margins within the given region.
-	pub fn margin(&mut self, state: &State, region: &Region) {
+	pub fn margin(&mut self, state: &State, config: &Config, region: &Region) {
 		let (rows, columns)    = (state.rows(), state.columns());
-		let (c, f, o, s, h, v) = (state.config(), state.font(), &mut self.context, state.config().style().spacing(), state.margin().horizontal, state.margin().vertical);
+		let (c, f, o, s, h, v) = (config, state.font(), &mut self.context, state.config().style().spacing(), state.margin().horizontal, state.margin().vertical);
 
 		// Bail out if there's no margin.
 		if h == 0 && v == 0 {
@@ -124,18 +151,20 @@ impl Renderer {
 	}
 
 	/// Draw the cursor.
-	fn cursor(&mut self, state: &State, cursor: &cursor::Cell, options: Options) {
+	fn cursor(&mut self, state: &State, config: &Config, cursor: &cursor::Cell, size: Size, options: Options) {
 		self.cache.invalidate(&cursor.cell());
 
-		let (c, o, f) = (state.config(), &mut self.context, state.font());
-		let cell = cursor.cell();
-		let bc   = options.blinking() && cursor.blink();
-		let fg   = cursor.foreground();
-		let bg   = cursor.background();
+		let (c, o, f) = (config, &mut self.context, state.font());
+		let cell  = cursor.cell();
+		let color = cell.style().resolve(c.color());
+		let bc    = options.blinking() && cursor.blink();
+		let fg    = cursor.foreground();
+		let bg    = cursor.background();
 
-		let w = f.width() * cell.width();
+		let s = if size == Size::Single { 1 } else { 2 };
+		let w = f.width() * cell.width() * s;
 		let h = f.height() + c.style().spacing();
-		let x = state.margin().horizontal + (cell.x() * f.width());
+		let x = state.margin().horizontal + (cell.x() * f.width() * s);
 		let y = state.margin().vertical + (cell.y() * h);
 
 		o.save();
@@ -155,7 +184,7 @@ impl Renderer {
 				}
 
 				Shape::Beam | Shape::Line => {
-					o.rgba(cell.style().background().unwrap_or(
+					o.rgba(color.background().unwrap_or(
 						c.style().color().background()));
 				}
 			}
@@ -172,7 +201,7 @@ impl Renderer {
 					}
 
 					Shape::Beam | Shape::Line => {
-						o.rgba(cell.style().foreground().unwrap_or(
+						o.rgba(color.foreground().unwrap_or(
 							c.style().color().foreground()));
 					}
 				}
@@ -221,18 +250,48 @@ impl Renderer {
 	}
 
 	/// Draw the given cell.
-	fn cell(&mut self, state: &State, cell: &cell::Position, options: Options) -> bool {
+	fn cell(&mut self, state: &State, config: &Config, cell: &cell::Position, size: Size, hover: Option<&Rc<Link>>, graphics: &[graphics::Visible], options: Options) -> bool {
+		// Check if the cell is part of the hyperlink under the mouse.
+		let hovered = match (cell.link(), hover) {
+			(Some(a), Some(b)) => Rc::ptr_eq(a, b),
+			_                  => false,
+		};
+
+		// Bail out if the cell is covered by a double size line, forgetting it so
+		// it's drawn again once the line is back to single size.
+		if size != Size::Single && cell.x() >= cmp::max(state.columns() / 2, 1) {
+			self.cache.invalidate(cell);
+			return false;
+		}
+
+		let s = if size == Size::Single { 1 } else { 2 };
+		let w = state.font().width() * cell.width();
+		let h = state.font().height() + config.style().spacing();
+		let x = state.margin().horizontal + (cell.x() * state.font().width() * s);
+		let y = state.margin().vertical + (cell.y() * h);
+
+		// Find the images covering the cell.
+		let images = graphics.iter().filter(|image| {
+			let left = state.margin().horizontal as f64 + (image.x * state.font().width() + image.offset.0) as f64;
+			let top  = state.margin().vertical as f64 + (image.y * h as i64 + image.offset.1 as i64) as f64;
+
+			cell.y() < image.limit &&
+			left < (x + w * s) as f64 && left + image.size.0 > x as f64 &&
+			top < (y + h) as f64 && top + image.size.1 > y as f64
+		}).collect::<Vec<_>>();
+
 		// Bail out if the cell is up to date.
-		if !self.cache.update(cell, options) && !options.damage() {
+		if !self.cache.update(cell, size, options, hovered, !images.is_empty()) && !options.damage() {
 			return false;
 		}
 
-		let (c, o, f) = (state.config(), &mut self.context, state.font());
+		let (c, o, f) = (config, &mut self.context, state.font());
+		let color     = cell.style().resolve(c.color());
 
-		let mut fg = cell.style().foreground().unwrap_or_else(||
+		let mut fg = color.foreground().unwrap_or_else(||
 			c.style().color().foreground());
 
-		let mut bg = cell.style().background().unwrap_or_else(||
+		let mut bg = color.background().unwrap_or_else(||
 			c.style().color().background());
 
 		if options.reverse() {
@@ -243,19 +302,44 @@ impl Renderer {
 			mem::swap(&mut fg, &mut bg);
 		}
 
-		let w = f.width() * cell.width();
-		let h = f.height() + c.style().spacing();
-		let x = state.margin().horizontal + (cell.x() * f.width());
-		let y = state.margin().vertical + (cell.y() * h);
+		let origin = (state.margin().horizontal, state.margin().vertical);
 
 		o.save();
 		{
 			// Draw the background.
-			o.rectangle(x as f64, y as f64, w as f64, h as f64);
+			o.rectangle(x as f64, y as f64, (w * s) as f64, h as f64);
 			o.clip();
 			o.rgba(bg);
 			o.paint();
 
+			// Draw the images below the text.
+			Renderer::images(o, images.iter().filter(|i| i.z < 0), origin, (f.width(), h));
+
+			// Double size lines are drawn scaled relative to the cell, showing only
+			// the relevant half for double height.
+			let (x, y) = match size {
+				Size::Single =>
+					(x, y),
+
+				Size::Wide => {
+					o.translate(x as f64, y as f64);
+					o.scale(2.0, 1.0);
+					(0, 0)
+				}
+
+				Size::Top => {
+					o.translate(x as f64, y as f64);
+					o.scale(2.0, 2.0);
+					(0, 0)
+				}
+
+				Size::Bottom => {
+					o.translate(x as f64, y as f64 - h as f64);
+					o.scale(2.0, 2.0);
+					(0, 0)
+				}
+			};
+
 			// Draw the glyph.
 			if !cell.style().attributes().contains(style::BLINK) || !options.blinking() {
 				if cell.is_occupied() {
@@ -270,11 +354,69 @@ impl Renderer {
 				}
 
 				// Draw underline.
-				if cell.style().attributes().contains(style::UNDERLINE) {
+				let attributes = cell.style().attributes();
+				if hovered || attributes.intersects(style::UNDERLINES) {
 					let (thickness, position) = f.underline();
+					let thickness             = cmp::max(thickness, 1) as f64;
+					let (x, w)                = (x as f64, w as f64);
 
-					o.rgba(c.style().color().underline().unwrap_or(fg));
-					o.rectangle(x as f64, (y + position) as f64, w as f64, thickness as f64);
+					o.rgba(color.underline().or(c.style().color().underline()).unwrap_or(fg));
+
+					if !hovered && attributes.contains(style::DOUBLE) {
+						let position = (position as f64).min(h as f64 - thickness * 3.0);
+
+						o.rectangle(x, y as f64 + position, w, thickness);
+						o.rectangle(x, y as f64 + position + thickness * 2.0, w, thickness);
+						o.fill();
+					}
+					else if !hovered && attributes.contains(style::CURLY) {
+						// One wave per column, kept inside the cell.
+						let amplitude = thickness * 1.5;
+						let center    = (position as f64 + thickness / 2.0).min(h as f64 - amplitude - thickness / 2.0);
+						let half      = f.width() as f64 / 2.0;
+
+						o.move_to(x, y as f64 + center);
+						for i in 0 .. cell.width() * 2 {
+							let start  = x + half * i as f64;
+							let offset = if i % 2 == 0 { -amplitude } else { amplitude };
+
+							o.curve_to(start + half / 2.0, y as f64 + center + offset,
+							           start + half / 2.0, y as f64 + center + offset,
+							           start + half, y as f64 + center);
+						}
+
+						o.line_width(thickness);
+						o.stroke();
+					}
+					else if !hovered && attributes.intersects(style::DOTTED | style::DASHED) {
+						// Offset the pattern by the position so it's continuous across cells.
+						let pattern = if attributes.contains(style::DOTTED) {
+							[thickness, thickness]
+						}
+						else {
+							[f.width() as f64 / 2.0, f.width() as f64 / 4.0]
+						};
+
+						o.move_to(x, y as f64 + position as f64 + thickness / 2.0);
+						o.line_to(x + w, y as f64 + position as f64 + thickness / 2.0);
+						o.dash(&pattern, x % (pattern[0] + pattern[1]));
+						o.line_width(thickness);
+						o.stroke();
+						o.dash(&[], 0.0);
+					}
+					else {
+						o.rectangle(x, (y + position) as f64, w, thickness);
+						o.line_width(1.0);
+						o.fill();
+					}
+				}
+
+				// Draw overline.
+				if attributes.contains(style::OVERLINE) {
+					let (thickness, _) = f.underline();
+
+					o.rgba(fg);
+					o.rectangle(x as f64, y as f64, w as f64, cmp::max(thickness, 1) as f64);
 					o.line_width(1.0);
 					o.fill();
 				}
@@ -292,6 +434,35 @@ impl Renderer {
 		}
 		o.restore();
 
+		// Draw the images above the text.
+		if images.iter().any(|i| i.z >= 0) {
+			o.save();
+			o.rectangle(x as f64, y as f64, (w * s) as f64, h as f64);
+			o.clip();
+			Renderer::images(o, images.iter().filter(|i| i.z >= 0), origin, (f.width(), h));
+			o.restore();
+		}
+
 		true
 	}
+
+	/// Draw the given images, the cell size is used to find their position
+	/// relative to the origin.
+	fn images<'a, I>(o: &mut cairo::Context, images: I, (x, y): (u32, u32), (width, height): (u32, u32))
+		where I: Iterator<Item = &'a &'a graphics::Visible<'a>>
+	{
+		for image in images {
+			let (sx, sy, sw, sh) = image.source;
+
+			o.save();
+			o.translate(
+				x as f64 + (image.x * width + image.offset.0) as f64,
+				y as f64 + (image.y * height as i64 + image.offset.1 as i64) as f64);
+			o.scale(image.size.0 / sw as f64, image.size.1 / sh as f64);
+			o.rectangle(0.0, 0.0, sw as f64, sh as f64);
+			o.clip();
+			o.image(image.image, -(sx as f64), -(sy as f64));
+			o.restore();
+		}
+	}
 }
@@ -16,6 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::cmp;
 
 use crate::util::clamp;
 use picto::Region;
@@ -117,6 +118,14 @@ impl State {
 		Some((x, y))
 	}
 
+	/// Find the position in pixels from the first cell, clamped to the cells.
+	pub fn pixels(&self, x: u32, y: u32) -> (u32, u32) {
+		let (f, h, v, s) = (&self.font, self.margin.horizontal, self.margin.vertical, self.config.style().spacing());
+
+		(cmp::min(x.saturating_sub(h), (self.columns() * f.width()).saturating_sub(1)),
+		 cmp::min(y.saturating_sub(v), (self.rows() * (f.height() + s)).saturating_sub(1)))
+	}
+
 	/// Turn the damaged region to cell-space.
 	pub fn damaged(&self, region: &Region) -> Region {
 		let (f, h, v, s) = (&self.font, self.margin.horizontal, self.margin.vertical, self.config.style().spacing());
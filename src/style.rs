@@ -16,16 +16,21 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use picto::color::Rgba;
+use crate::config::Color;
 
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub struct Style {
 	pub foreground: Option<Rgba<f64>>,
 	pub background: Option<Rgba<f64>>,
+	pub underline:  Option<Rgba<f64>>,
 	pub attributes: Attributes,
+
+	/// The palette indices the colors came from, if any.
+	pub palette: [Option<u8>; 3],
 }
 
 bitflags! {
-	pub struct Attributes: u8 {
+	pub struct Attributes: u16 {
 		const NONE      = 0;
 		const BOLD      = 1 << 0;
 		const FAINT     = 1 << 1;
@@ -35,6 +40,18 @@ bitflags! {
 		const REVERSE   = 1 << 5;
 		const INVISIBLE = 1 << 6;
 		const STRUCK    = 1 << 7;
+		const DOUBLE    = 1 << 8;
+		const CURLY     = 1 << 9;
+		const DOTTED    = 1 << 10;
+		const DASHED    = 1 << 11;
+		const OVERLINE  = 1 << 12;
+		const PROTECTED = 1 << 13;
+
+		const UNDERLINES = UNDERLINE.bits |
+		                   DOUBLE.bits    |
+		                   CURLY.bits     |
+		                   DOTTED.bits    |
+		                   DASHED.bits;
 	}
 }
 
@@ -43,7 +60,10 @@ impl Default for Style {
 		Style {
 			foreground: None,
 			background: None,
+			underline:  None,
 			attributes: Attributes::empty(),
+
+			palette: [None; 3],
 		}
 	}
 }
@@ -57,7 +77,31 @@ impl Style {
 		self.background.as_ref()
 	}
 
+	/// The color of the underline, if it differs from the foreground.
+	pub fn underline(&self) -> Option<&Rgba<f64>> {
+		self.underline.as_ref()
+	}
+
 	pub fn attributes(&self) -> Attributes {
 		self.attributes
 	}
+
+	/// Look the indexed colors up in the given palette.
+	pub fn resolve(&self, palette: &Color) -> Style {
+		let mut style = *self;
+
+		if let Some(n) = self.palette[0] {
+			style.foreground = Some(*palette.get(n));
+		}
+
+		if let Some(n) = self.palette[1] {
+			style.background = Some(*palette.get(n));
+		}
+
+		if let Some(n) = self.palette[2] {
+			style.underline = Some(*palette.get(n));
+		}
+
+		style
+	}
 }
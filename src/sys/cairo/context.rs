@@ -16,6 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::mem;
+use std::ffi::CString;
 
 use crate::ffi::cairo::*;
 use crate::ffi::pango::*;
@@ -62,6 +63,18 @@ impl Context {
 		}
 	}
 
+	pub fn translate(&mut self, x: f64, y: f64) {
+		unsafe {
+			cairo_translate(self.0, x, y);
+		}
+	}
+
+	pub fn scale(&mut self, x: f64, y: f64) {
+		unsafe {
+			cairo_scale(self.0, x, y);
+		}
+	}
+
 	pub fn clip(&mut self) {
 		unsafe {
 			cairo_clip(self.0);
@@ -92,6 +105,51 @@ impl Context {
 		}
 	}
 
+	pub fn curve_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) {
+		unsafe {
+			cairo_curve_to(self.0, x1, y1, x2, y2, x3, y3);
+		}
+	}
+
+	pub fn arc(&mut self, x: f64, y: f64, radius: f64, start: f64, end: f64) {
+		unsafe {
+			cairo_arc(self.0, x, y, radius, start, end);
+		}
+	}
+
+	pub fn arc_negative(&mut self, x: f64, y: f64, radius: f64, start: f64, end: f64) {
+		unsafe {
+			cairo_arc_negative(self.0, x, y, radius, start, end);
+		}
+	}
+
+	pub fn position(&self) -> (f64, f64) {
+		unsafe {
+			let mut x = 0.0;
+			let mut y = 0.0;
+			cairo_get_current_point(self.0, &mut x, &mut y);
+
+			(x, y)
+		}
+	}
+
+	/// Draw text at the current point with the default monospace font.
+	pub fn text<T: AsRef<str>>(&mut self, text: T, size: f64) {
+		if let Ok(text) = CString::new(text.as_ref()) {
+			unsafe {
+				cairo_select_font_face(self.0, b"monospace\0".as_ptr() as *const _, 0, 0);
+				cairo_set_font_size(self.0, size);
+				cairo_show_text(self.0, text.as_ptr());
+			}
+		}
+	}
+
+	pub fn dash(&mut self, dashes: &[f64], offset: f64) {
+		unsafe {
+			cairo_set_dash(self.0, dashes.as_ptr(), dashes.len() as i32, offset);
+		}
+	}
+
 	pub fn line_width(&mut self, w: f64) {
 		unsafe {
 			cairo_set_line_width(self.0, w);
@@ -18,8 +18,9 @@
 use std::ptr;
 use std::cell::Cell;
 
-use libc::{c_int};
+use libc::c_int;
 use crate::ffi::cairo::*;
+use super::Surface;
 
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Image {
@@ -60,6 +61,31 @@ impl Image {
 		self.stride
 	}
 
+	/// Create a surface drawing into the image.
+	///
+	/// # Safety
+	///
+	/// The surface, and any context created on it, borrow the image buffer
+	/// without a lifetime, so they must be dropped before the image is
+	/// dropped or replaced.
+	pub unsafe fn surface(&mut self) -> Surface {
+		self.invalidate();
+
+		Surface(cairo_image_surface_create_for_data(self.buffer.as_ptr(), cairo_format_t::Argb32,
+			self.width as c_int, self.height as c_int, self.stride as c_int))
+	}
+
+	/// Drop the cached pattern, since the content changed.
+	fn invalidate(&mut self) {
+		unsafe {
+			if let Some(ptr) = self.pattern.get().as_mut() {
+				cairo_pattern_destroy(ptr);
+			}
+
+			self.pattern.set(ptr::null_mut());
+		}
+	}
+
 	pub fn set(&mut self, x: u32, y: u32, &(r, g, b, a): &(u8, u8, u8, u8)) {
 		let offset = ((x * 4) + (y * self.stride)) as usize;
 
@@ -23,6 +23,7 @@ use tendril::StrTendril;
 
 use crate::style::Style;
 use crate::sys::cairo;
+use crate::terminal::Link;
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum Cell {
@@ -38,6 +39,7 @@ pub enum Cell {
 	Occupied {
 		style: Rc<Style>,
 		value: StrTendril,
+		link:  Option<Rc<Link>>,
 	},
 
 	Reference(u8),
@@ -72,6 +74,7 @@ impl Cell {
 		Cell::Occupied {
 			value: value.into(),
 			style: style,
+			link:  None,
 		}
 	}
 
@@ -159,6 +162,7 @@ impl Cell {
 		mem::replace(self, Cell::Occupied {
 			value: value.into().into(),
 			style: style,
+			link:  None,
 		});
 	}
 
@@ -194,6 +198,23 @@ impl Cell {
 		}
 	}
 
+	/// Change the hyperlink in place, only occupied cells can have one.
+	pub fn set_link(&mut self, value: Option<Rc<Link>>) {
+		if let Cell::Occupied { ref mut link, .. } = *self {
+			*link = value;
+		}
+	}
+
+	/// Get the hyperlink if any.
+	pub fn link(&self) -> Option<&Rc<Link>> {
+		if let Cell::Occupied { ref link, .. } = *self {
+			link.as_ref()
+		}
+		else {
+			None
+		}
+	}
+
 	/// Get the cell style.
 	pub fn style(&self) -> &Rc<Style> {
 		match *self {
@@ -0,0 +1,389 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::control::DEC;
+use crate::control::DEC::charset::{NRCS, ISO};
+
+/// Translate a character in the GL range through the given charset, national
+/// replacement charsets are only used when `nrcs` is enabled.
+pub fn translate(charset: DEC::Charset, nrcs: bool, ch: char) -> Option<char> {
+	if ch < '\x21' || ch > '\x7E' {
+		return None;
+	}
+
+	match charset {
+		DEC::Charset::DEC(DEC::charset::DEC::Graphic) =>
+			graphic(ch),
+
+		DEC::Charset::DEC(DEC::charset::DEC::Supplemental) =>
+			supplemental(ch),
+
+		DEC::Charset::DEC(DEC::charset::DEC::Technical) =>
+			technical(ch),
+
+		// The UK charset shares its final byte with Latin-1.
+		DEC::Charset::ISO(ISO::Latin1) if nrcs =>
+			national(NRCS::UK, ch),
+
+		DEC::Charset::NRCS(set) if nrcs =>
+			national(set, ch),
+
+		_ =>
+			None
+	}
+}
+
+/// Translate a character in the GR range through the given charset, only the
+/// DEC charsets are applied since the others are either 94 characters wide or
+/// already match the input.
+///
+/// Like xterm this should only be used when UTF-8 mode is off.
+pub fn upper(charset: DEC::Charset, ch: char) -> Option<char> {
+	if ch < '\u{A1}' || ch > '\u{FE}' {
+		return None;
+	}
+
+	match charset {
+		DEC::Charset::DEC(..) =>
+			translate(charset, false, (ch as u8 - 0x80) as char),
+
+		_ =>
+			None
+	}
+}
+
+/// The DEC Special Graphics charset.
+fn graphic(ch: char) -> Option<char> {
+	Some(match ch {
+		'A' => '↑',
+		'B' => '↓',
+		'C' => '→',
+		'D' => '←',
+		'E' => '█',
+		'F' => '▚',
+		'G' => '☃',
+		'_' => ' ',
+		'`' => '◆',
+		'a' => '▒',
+		'b' => '␉',
+		'c' => '␌',
+		'd' => '␍',
+		'e' => '␊',
+		'f' => '°',
+		'g' => '±',
+		'h' => '␤',
+		'i' => '␋',
+		'j' => '┘',
+		'k' => '┐',
+		'l' => '┌',
+		'm' => '└',
+		'n' => '┼',
+		'o' => '⎺',
+		'p' => '⎻',
+		'q' => '─',
+		'r' => '⎼',
+		's' => '⎽',
+		't' => '├',
+		'u' => '┤',
+		'v' => '┴',
+		'w' => '┬',
+		'x' => '│',
+		'y' => '≤',
+		'z' => '≥',
+		'{' => 'π',
+		'|' => '≠',
+		'}' => '£',
+		'~' => '·',
+		_   => return None,
+	})
+}
+
+/// The DEC Supplemental Graphics charset, it matches the upper half of
+/// Latin-1 except for a few characters.
+fn supplemental(ch: char) -> Option<char> {
+	Some(match ch {
+		'(' => '¤',
+		'W' => 'Œ',
+		']' => 'Ÿ',
+		'w' => 'œ',
+		'}' => 'ÿ',
+
+		// Undefined positions.
+		'$' | '&' | ',' | '-' | '.' | '/' | '4' | '8' | '>' | 'P' | '^' | 'p' | '~' =>
+			return None,
+
+		ch => (ch as u8 + 0x80) as char,
+	})
+}
+
+/// The DEC Technical charset.
+fn technical(ch: char) -> Option<char> {
+	Some(match ch {
+		'!' => '⎷',
+		'"' => '┌',
+		'#' => '─',
+		'$' => '⌠',
+		'%' => '⌡',
+		'&' => '│',
+		'\'' => '⎡',
+		'(' => '⎣',
+		')' => '⎤',
+		'*' => '⎦',
+		'+' => '⎛',
+		',' => '⎝',
+		'-' => '⎞',
+		'.' => '⎠',
+		'/' => '⎨',
+		'0' => '⎬',
+		'<' => '≤',
+		'=' => '≠',
+		'>' => '≥',
+		'?' => '∫',
+		'@' => '∴',
+		'A' => '∝',
+		'B' => '∞',
+		'C' => '÷',
+		'D' => 'Δ',
+		'E' => '∇',
+		'F' => 'Φ',
+		'G' => 'Γ',
+		'H' => '∼',
+		'I' => '≃',
+		'J' => 'Θ',
+		'K' => '×',
+		'L' => 'Λ',
+		'M' => '⇔',
+		'N' => '⇒',
+		'O' => '≡',
+		'P' => 'Π',
+		'Q' => 'Ψ',
+		'S' => 'Σ',
+		'V' => '√',
+		'W' => 'Ω',
+		'X' => 'Ξ',
+		'Y' => 'Υ',
+		'Z' => '⊂',
+		'[' => '⊃',
+		'\\' => '∩',
+		']' => '∪',
+		'^' => '∧',
+		'_' => '∨',
+		'`' => '¬',
+		'a' => 'α',
+		'b' => 'β',
+		'c' => 'χ',
+		'd' => 'δ',
+		'e' => 'ε',
+		'f' => 'φ',
+		'g' => 'γ',
+		'h' => 'η',
+		'i' => 'ι',
+		'j' => 'θ',
+		'k' => 'κ',
+		'l' => 'λ',
+		'n' => 'ν',
+		'o' => '∂',
+		'p' => 'π',
+		'q' => 'ψ',
+		'r' => 'ρ',
+		's' => 'σ',
+		't' => 'τ',
+		'v' => 'ƒ',
+		'w' => 'ω',
+		'x' => 'ξ',
+		'y' => 'υ',
+		'z' => 'ζ',
+		'{' => '←',
+		'|' => '↑',
+		'}' => '→',
+		'~' => '↓',
+		_   => return None,
+	})
+}
+
+/// The national replacement charsets, only the replaced positions are listed.
+fn national(set: NRCS, ch: char) -> Option<char> {
+	Some(match (set, ch) {
+		(NRCS::UK, '#') => '£',
+
+		(NRCS::French, '#') => '£',
+		(NRCS::French, '@') => 'à',
+		(NRCS::French, '[') => '°',
+		(NRCS::French, '\\') => 'ç',
+		(NRCS::French, ']') => '§',
+		(NRCS::French, '{') => 'é',
+		(NRCS::French, '|') => 'ù',
+		(NRCS::French, '}') => 'è',
+		(NRCS::French, '~') => '¨',
+
+		(NRCS::FrenchCanadian, '@') => 'à',
+		(NRCS::FrenchCanadian, '[') => 'â',
+		(NRCS::FrenchCanadian, '\\') => 'ç',
+		(NRCS::FrenchCanadian, ']') => 'ê',
+		(NRCS::FrenchCanadian, '^') => 'î',
+		(NRCS::FrenchCanadian, '`') => 'ô',
+		(NRCS::FrenchCanadian, '{') => 'é',
+		(NRCS::FrenchCanadian, '|') => 'ù',
+		(NRCS::FrenchCanadian, '}') => 'è',
+		(NRCS::FrenchCanadian, '~') => 'û',
+
+		(NRCS::Norwegian, '@') => 'Ä',
+		(NRCS::Norwegian, '[') => 'Æ',
+		(NRCS::Norwegian, '\\') => 'Ø',
+		(NRCS::Norwegian, ']') => 'Å',
+		(NRCS::Norwegian, '^') => 'Ü',
+		(NRCS::Norwegian, '`') => 'ä',
+		(NRCS::Norwegian, '{') => 'æ',
+		(NRCS::Norwegian, '|') => 'ø',
+		(NRCS::Norwegian, '}') => 'å',
+		(NRCS::Norwegian, '~') => 'ü',
+
+		(NRCS::Finnish, '[') => 'Ä',
+		(NRCS::Finnish, '\\') => 'Ö',
+		(NRCS::Finnish, ']') => 'Å',
+		(NRCS::Finnish, '^') => 'Ü',
+		(NRCS::Finnish, '`') => 'é',
+		(NRCS::Finnish, '{') => 'ä',
+		(NRCS::Finnish, '|') => 'ö',
+		(NRCS::Finnish, '}') => 'å',
+		(NRCS::Finnish, '~') => 'ü',
+
+		(NRCS::German, '@') => '§',
+		(NRCS::German, '[') => 'Ä',
+		(NRCS::German, '\\') => 'Ö',
+		(NRCS::German, ']') => 'Ü',
+		(NRCS::German, '{') => 'ä',
+		(NRCS::German, '|') => 'ö',
+		(NRCS::German, '}') => 'ü',
+		(NRCS::German, '~') => 'ß',
+
+		(NRCS::Italian, '#') => '£',
+		(NRCS::Italian, '@') => '§',
+		(NRCS::Italian, '[') => '°',
+		(NRCS::Italian, '\\') => 'ç',
+		(NRCS::Italian, ']') => 'é',
+		(NRCS::Italian, '`') => 'ù',
+		(NRCS::Italian, '{') => 'à',
+		(NRCS::Italian, '|') => 'ò',
+		(NRCS::Italian, '}') => 'è',
+		(NRCS::Italian, '~') => 'ì',
+
+		(NRCS::Swiss, '#') => 'ù',
+		(NRCS::Swiss, '@') => 'à',
+		(NRCS::Swiss, '[') => 'é',
+		(NRCS::Swiss, '\\') => 'ç',
+		(NRCS::Swiss, ']') => 'ê',
+		(NRCS::Swiss, '^') => 'î',
+		(NRCS::Swiss, '_') => 'è',
+		(NRCS::Swiss, '`') => 'ô',
+		(NRCS::Swiss, '{') => 'ä',
+		(NRCS::Swiss, '|') => 'ö',
+		(NRCS::Swiss, '}') => 'ü',
+		(NRCS::Swiss, '~') => 'û',
+
+		(NRCS::Swedish, '@') => 'É',
+		(NRCS::Swedish, '[') => 'Ä',
+		(NRCS::Swedish, '\\') => 'Ö',
+		(NRCS::Swedish, ']') => 'Å',
+		(NRCS::Swedish, '^') => 'Ü',
+		(NRCS::Swedish, '`') => 'é',
+		(NRCS::Swedish, '{') => 'ä',
+		(NRCS::Swedish, '|') => 'ö',
+		(NRCS::Swedish, '}') => 'å',
+		(NRCS::Swedish, '~') => 'ü',
+
+		(NRCS::Spanish, '#') => '£',
+		(NRCS::Spanish, '@') => '§',
+		(NRCS::Spanish, '[') => '¡',
+		(NRCS::Spanish, '\\') => 'Ñ',
+		(NRCS::Spanish, ']') => '¿',
+		(NRCS::Spanish, '{') => '°',
+		(NRCS::Spanish, '|') => 'ñ',
+		(NRCS::Spanish, '}') => 'ç',
+
+		(NRCS::Portuguese, '[') => 'Ã',
+		(NRCS::Portuguese, '\\') => 'Ç',
+		(NRCS::Portuguese, ']') => 'Õ',
+		(NRCS::Portuguese, '{') => 'ã',
+		(NRCS::Portuguese, '|') => 'ç',
+		(NRCS::Portuguese, '}') => 'õ',
+
+		_ => return None,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GRAPHIC:      DEC::Charset = DEC::Charset::DEC(DEC::charset::DEC::Graphic);
+	const SUPPLEMENTAL: DEC::Charset = DEC::Charset::DEC(DEC::charset::DEC::Supplemental);
+	const TECHNICAL:    DEC::Charset = DEC::Charset::DEC(DEC::charset::DEC::Technical);
+
+	#[test]
+	fn ascii() {
+		assert_eq!(translate(DEC::Charset::ISO(ISO::Latin1), false, 'a'), None);
+		assert_eq!(translate(DEC::Charset::UserPreferred, true, 'a'), None);
+	}
+
+	#[test]
+	fn graphic() {
+		assert_eq!(translate(GRAPHIC, false, 'q'), Some('─'));
+		assert_eq!(translate(GRAPHIC, false, 'x'), Some('│'));
+		assert_eq!(translate(GRAPHIC, false, '`'), Some('◆'));
+		assert_eq!(translate(GRAPHIC, false, 'A'), Some('↑'));
+		assert_eq!(translate(GRAPHIC, false, '0'), None);
+		assert_eq!(translate(GRAPHIC, false, ' '), None);
+	}
+
+	#[test]
+	fn supplemental() {
+		assert_eq!(translate(SUPPLEMENTAL, false, 'A'), Some('Á'));
+		assert_eq!(translate(SUPPLEMENTAL, false, '('), Some('¤'));
+		assert_eq!(translate(SUPPLEMENTAL, false, 'W'), Some('Œ'));
+		assert_eq!(translate(SUPPLEMENTAL, false, '$'), None);
+	}
+
+	#[test]
+	fn technical() {
+		assert_eq!(translate(TECHNICAL, false, 'B'), Some('∞'));
+		assert_eq!(translate(TECHNICAL, false, 'p'), Some('π'));
+		assert_eq!(translate(TECHNICAL, false, '1'), None);
+	}
+
+	#[test]
+	fn national() {
+		let german = DEC::Charset::NRCS(NRCS::German);
+
+		assert_eq!(translate(german, false, '['), None);
+		assert_eq!(translate(german, true, '['), Some('Ä'));
+		assert_eq!(translate(german, true, '~'), Some('ß'));
+		assert_eq!(translate(german, true, 'a'), None);
+
+		assert_eq!(translate(DEC::Charset::ISO(ISO::Latin1), false, '#'), None);
+		assert_eq!(translate(DEC::Charset::ISO(ISO::Latin1), true, '#'), Some('£'));
+	}
+
+	#[test]
+	fn gr() {
+		assert_eq!(upper(GRAPHIC, '\u{F1}'), Some('─'));
+		assert_eq!(upper(SUPPLEMENTAL, '\u{C1}'), Some('Á'));
+		assert_eq!(upper(DEC::Charset::ISO(ISO::Latin1), '\u{C1}'), None);
+		assert_eq!(upper(GRAPHIC, '\u{A0}'), None);
+		assert_eq!(upper(GRAPHIC, 'q'), None);
+	}
+}
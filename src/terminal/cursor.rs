@@ -16,13 +16,14 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ops::Deref;
+use std::cmp;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::convert::TryFrom;
 use std::i32;
 
 use picto::color::Rgba;
-use crate::style::Style;
+use crate::style::{self, Style};
 use crate::config::Config;
 use crate::config::style::Shape;
 use crate::control::DEC;
@@ -38,11 +39,13 @@ pub struct Cursor {
 
 	pub state:  State,
 	pub scroll: (u32, u32),
+	pub margin: (u32, u32),
 	pub style:  Rc<Style>,
 	pub bright: Option<u8>,
 
 	pub charsets: [DEC::Charset; 4],
 	pub charset:  u8,
+	pub right:    u8,
 
 	pub foreground: Rgba<f64>,
 	pub background: Rgba<f64>,
@@ -51,8 +54,12 @@ pub struct Cursor {
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Travel {
+	/// Move relative to the margins when in origin mode.
 	Position(Option<u32>, Option<u32>),
 
+	/// Move to an absolute position.
+	Absolute(Option<u32>, Option<u32>),
+
 	Up(u32),
 	Down(u32),
 	Left(u32),
@@ -65,6 +72,9 @@ bitflags! {
 		const VISIBLE = 1 << 1;
 		const WRAP    = 1 << 2;
 		const ORIGIN  = 1 << 3;
+		const DOUBLE  = 1 << 4;
+
+		const PROTECTED = 1 << 5;
 	}
 }
 
@@ -94,11 +104,13 @@ impl Cursor {
 
 			state:  state,
 			scroll: (0, height - 1),
+			margin: (0, width - 1),
 			style:  Default::default(),
 			bright: None,
 
 			charsets: [DEC::charset::ISO::Latin2.into(); 4],
 			charset:  0,
+			right:    2,
 
 			foreground: *config.style().cursor().foreground(),
 			background: *config.style().cursor().background(),
@@ -112,6 +124,10 @@ impl Cursor {
 			self.scroll = (0, height - 1);
 		}
 
+		if self.margin == (0, self.width - 1) || self.margin.1 >= width {
+			self.margin = (0, width - 1);
+		}
+
 		if self.x >= width {
 			self.x = width - 1;
 		}
@@ -124,6 +140,35 @@ impl Cursor {
 		self.height = height;
 	}
 
+	/// Restore the state saved by DECSC from the given cursor.
+	pub fn restore(&mut self, saved: &Cursor) {
+		self.x = cmp::min(saved.x, self.width - 1);
+		self.y = cmp::min(saved.y, self.height - 1);
+
+		self.style    = saved.style.clone();
+		self.bright   = saved.bright;
+		self.charsets = saved.charsets;
+		self.charset  = saved.charset;
+		self.right    = saved.right;
+
+		self.state.remove(ORIGIN | WRAP | PROTECTED);
+		self.state.insert(saved.state & (ORIGIN | WRAP | PROTECTED));
+	}
+
+	/// Reset the state affected by DECSTR, the position is left untouched.
+	pub fn reset(&mut self) {
+		self.state.remove(ORIGIN | WRAP | PROTECTED);
+		self.state.insert(VISIBLE);
+
+		self.scroll   = (0, self.height - 1);
+		self.margin   = (0, self.width - 1);
+		self.style    = Default::default();
+		self.bright   = None;
+		self.charsets = [DEC::charset::ISO::Latin2.into(); 4];
+		self.charset  = 0;
+		self.right    = 2;
+	}
+
 	/// Get the position.
 	pub fn position(&self) -> (u32, u32) {
 		(self.x, self.y)
@@ -179,8 +224,67 @@ impl Cursor {
 		self.scroll
 	}
 
+	/// Get the left and right margins.
+	pub fn margin(&self) -> (u32, u32) {
+		self.margin
+	}
+
+	/// Get the column a carriage return moves to.
+	pub fn start(&self) -> u32 {
+		if self.x < self.margin.0 {
+			0
+		}
+		else {
+			self.margin.0
+		}
+	}
+
+	/// Get the column wrapping happens at.
+	pub fn end(&self) -> u32 {
+		if self.x > self.margin.1 {
+			self.edge()
+		}
+		else {
+			cmp::min(self.margin.1, self.edge())
+		}
+	}
+
+	/// Get the last usable column of the current line, which is halved on
+	/// double width lines.
+	pub fn edge(&self) -> u32 {
+		if self.state.contains(DOUBLE) {
+			cmp::max(self.width / 2, 1) - 1
+		}
+		else {
+			self.width - 1
+		}
+	}
+
+	/// Change whether the current line is double width, keeping the cursor
+	/// within it.
+	pub fn line(&mut self, double: bool) {
+		if double {
+			self.state.insert(DOUBLE);
+		}
+		else {
+			self.state.remove(DOUBLE);
+		}
+
+		if self.x > self.edge() {
+			self.x = self.edge();
+		}
+	}
+
 	/// Update the current style if needed.
-	pub fn update(&mut self, style: Style) {
+	pub fn update(&mut self, mut style: Style) {
+		// The protection is only changed through DECSCA.
+		if self.state.contains(PROTECTED) {
+			style.attributes.insert(style::PROTECTED);
+		}
+		else {
+			style.attributes.remove(style::PROTECTED);
+		}
+
 		if &*self.style != &style {
 			self.style = Rc::new(style);
 		}
@@ -195,27 +299,23 @@ impl Cursor {
 		let mut overflow = None;
 
 		match value {
-			Position(x, y) => {
+			Position(x, y) if self.state.contains(ORIGIN) => {
+				if let Some(x) = x {
+					self.x = cmp::min(x.saturating_add(self.margin.0), self.margin.1);
+				}
+
+				if let Some(y) = y {
+					self.y = cmp::min(y.saturating_add(self.scroll.0), self.scroll.1);
+				}
+			}
+
+			Position(x, y) | Absolute(x, y) => {
 				if let Some(x) = x {
-					if x >= self.width {
-						self.x = self.width - 1;
-					}
-					else {
-						self.x = x;
-					}
+					self.x = cmp::min(x, self.width - 1);
 				}
 
-				if let Some(mut y) = y {
-					if self.state.contains(ORIGIN) {
-						y += self.scroll.0;
-					}
-
-					if y >= self.height {
-						self.y = self.height - 1;
-					}
-					else {
-						self.y = y;
-					}
+				if let Some(y) = y {
+					self.y = cmp::min(y, self.height - 1);
 				}
 			}
 
@@ -244,11 +344,12 @@ impl Cursor {
 			}
 
 			Left(n) => {
-				let new = (self.x as i32).saturating_sub(i32::try_from(n).unwrap_or(i32::MAX));
+				let new  = (self.x as i32).saturating_sub(i32::try_from(n).unwrap_or(i32::MAX));
+				let edge = if self.x >= self.margin.0 { self.margin.0 as i32 } else { 0 };
 
-				if new < 0 {
-					self.x = 0;
-					overflow = Some(new.abs());
+				if new < edge {
+					self.x = edge as u32;
+					overflow = Some(edge - new);
 				}
 				else {
 					self.x = new as u32;
@@ -256,11 +357,12 @@ impl Cursor {
 			}
 
 			Right(n) => {
-				let new = (self.x as i32).saturating_add(i32::try_from(n).unwrap_or(i32::MAX));
+				let new  = (self.x as i32).saturating_add(i32::try_from(n).unwrap_or(i32::MAX));
+				let edge = self.end() as i32;
 
-				if new >= self.width as i32 {
-					self.x = self.width - 1;
-					overflow = Some(new - (self.width as i32 - 1));
+				if new > edge {
+					self.x = edge as u32;
+					overflow = Some(new - edge);
 				}
 				else {
 					self.x = new as u32;
@@ -18,7 +18,7 @@
 use std::rc::Rc;
 use std::collections::{VecDeque, LinkedList};
 
-use crate::terminal::{Cell, Row};
+use crate::terminal::{Cell, Row, Size};
 use crate::style::Style;
 
 /// Wrapper for `Row` reuse.
@@ -52,6 +52,8 @@ impl Free {
 		match self.inner.pop_front() {
 			Some(mut row) => {
 				row.wrapped = false;
+				row.marks.clear();
+				row.size = Size::Single;
 				row.resize(cols, Cell::empty(self.empty.clone()));
 
 				for cell in row.iter_mut().filter(|c| !c.is_default()) {
@@ -65,6 +67,8 @@ impl Free {
 				Row {
 					inner:   vec_deque![Cell::empty(self.empty.clone()); cols],
 					wrapped: false,
+					marks:   Vec::new(),
+					size:    Size::Single,
 				}
 			}
 		}
@@ -0,0 +1,784 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::env;
+use std::mem;
+use std::cmp;
+use fnv::FnvHasher;
+
+use picto::read;
+use picto::color::Rgba;
+use crate::util;
+use crate::sys::cairo;
+
+/// Maximum amount of memory used by stored images.
+pub const LIMIT: usize = 256 * 1024 * 1024;
+
+/// Images transmitted through the kitty graphics protocol.
+///
+/// Images are kept outside of the grid and placements are anchored to a row,
+/// which moves along with the text when the view scrolls.
+#[derive(Debug)]
+pub struct Graphics {
+	images:  HashMap<u32, Entry, BuildHasherDefault<FnvHasher>>,
+	size:    usize,
+	serial:  u64,
+	next:    u32,
+	pending: Option<(Control, String)>,
+
+	placements: Vec<Placement>,
+	alternate:  Vec<Placement>,
+}
+
+#[derive(Debug)]
+struct Entry {
+	number: u32,
+	serial: u64,
+	image:  cairo::Image,
+}
+
+/// The control data of a graphics command.
+#[derive(Clone, Debug)]
+pub struct Control {
+	pub action:      u8,
+	pub quiet:       u8,
+	pub format:      u32,
+	pub medium:      u8,
+	pub compression: Option<u8>,
+	pub more:        bool,
+	pub id:          u32,
+	pub number:      u32,
+	pub placement:   u32,
+	pub width:       u32,
+	pub height:      u32,
+	pub size:        usize,
+	pub from:        usize,
+	pub source:      (u32, u32, u32, u32),
+	pub offset:      (u32, u32),
+	pub cells:       (u32, u32),
+	pub z:           i32,
+	pub cursor:      u32,
+	pub delete:      u8,
+}
+
+impl Default for Control {
+	fn default() -> Self {
+		Control {
+			action:      b't',
+			quiet:       0,
+			format:      32,
+			medium:      b'd',
+			compression: None,
+			more:        false,
+			id:          0,
+			number:      0,
+			placement:   0,
+			width:       0,
+			height:      0,
+			size:        0,
+			from:        0,
+			source:      (0, 0, 0, 0),
+			offset:      (0, 0),
+			cells:       (0, 0),
+			z:           0,
+			cursor:      0,
+			delete:      b'a',
+		}
+	}
+}
+
+/// An image placed on the grid.
+#[derive(Clone, Debug)]
+struct Placement {
+	image:  u32,
+	id:     u32,
+	x:      u32,
+	y:      i64,
+	offset: (u32, u32),
+	source: (u32, u32, u32, u32),
+	size:   (f64, f64),
+	cells:  (u32, u32),
+	z:      i32,
+}
+
+impl Placement {
+	/// Check if the placement covers the given cell.
+	fn contains(&self, x: u32, y: i64) -> bool {
+		self.column(x) && self.row(y)
+	}
+
+	/// Check if the placement covers the given column.
+	fn column(&self, x: u32) -> bool {
+		x >= self.x && x < self.x + self.cells.0
+	}
+
+	/// Check if the placement covers the given row.
+	fn row(&self, y: i64) -> bool {
+		y >= self.y && y < self.y + self.cells.1 as i64
+	}
+}
+
+/// A placement as seen from the current view.
+#[derive(Debug)]
+pub struct Visible<'a> {
+	/// The image data.
+	pub image: &'a cairo::Image,
+
+	/// The column and row the image is anchored to.
+	pub x: u32,
+	pub y: i64,
+
+	/// The pixel offset within the anchor cell.
+	pub offset: (u32, u32),
+
+	/// The part of the image being shown.
+	pub source: (u32, u32, u32, u32),
+
+	/// The size in pixels the source is scaled to.
+	pub size: (f64, f64),
+
+	/// The stacking order, negative values are drawn below the text.
+	pub z: i32,
+
+	/// The first row the image cannot be drawn on.
+	pub limit: u32,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Error {
+	Invalid(&'static str),
+	NotFound(&'static str),
+	NoData(&'static str),
+	TooBig,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Invalid(message) =>
+				write!(f, "EINVAL:{}", message),
+
+			Error::NotFound(message) =>
+				write!(f, "ENOENT:{}", message),
+
+			Error::NoData(message) =>
+				write!(f, "ENODATA:{}", message),
+
+			Error::TooBig =>
+				f.write_str("EFBIG:image too big"),
+		}
+	}
+}
+
+/// Parse the body of a graphics command, without the leading `G`.
+pub fn parse(string: &str) -> Option<(Control, &str)> {
+	let mut parts   = string.splitn(2, ';');
+	let     keys    = parts.next()?;
+	let     payload = parts.next().unwrap_or("");
+	let mut control = Control::default();
+
+	for pair in keys.split(',').filter(|p| !p.is_empty()) {
+		let mut pair  = pair.splitn(2, '=');
+		let     key   = pair.next()?;
+		let     value = pair.next()?;
+
+		match key {
+			"a" => control.action      = *value.as_bytes().first()?,
+			"q" => control.quiet       = value.parse().ok()?,
+			"f" => control.format      = value.parse().ok()?,
+			"t" => control.medium      = *value.as_bytes().first()?,
+			"o" => control.compression = value.as_bytes().first().cloned(),
+			"m" => control.more        = value == "1",
+			"i" => control.id          = value.parse().ok()?,
+			"I" => control.number      = value.parse().ok()?,
+			"p" => control.placement   = value.parse().ok()?,
+			"s" => control.width       = value.parse().ok()?,
+			"v" => control.height      = value.parse().ok()?,
+			"S" => control.size        = value.parse().ok()?,
+			"O" => control.from        = value.parse().ok()?,
+			"x" => control.source.0    = value.parse().ok()?,
+			"y" => control.source.1    = value.parse().ok()?,
+			"w" => control.source.2    = value.parse().ok()?,
+			"h" => control.source.3    = value.parse().ok()?,
+			"X" => control.offset.0    = value.parse().ok()?,
+			"Y" => control.offset.1    = value.parse().ok()?,
+			"c" => control.cells.0     = value.parse().ok()?,
+			"r" => control.cells.1     = value.parse().ok()?,
+			"z" => control.z           = value.parse().ok()?,
+			"C" => control.cursor      = value.parse().ok()?,
+			"d" => control.delete      = *value.as_bytes().first()?,
+			_   => (),
+		}
+	}
+
+	Some((control, payload))
+}
+
+/// Get the dimensions of a PNG, GIF, JPEG or BMP image from its header.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+	png(data).or_else(|| gif(data)).or_else(|| jpeg(data)).or_else(|| bmp(data))
+}
+
+/// Get the dimensions of a PNG image from its header.
+fn png(data: &[u8]) -> Option<(u32, u32)> {
+	if data.len() < 24 || &data[.. 8] != b"\x89PNG\r\n\x1A\n" || &data[12 .. 16] != b"IHDR" {
+		return None;
+	}
+
+	let width  = u32::from_be_bytes([data[16], data[17], data[18], data[19]]);
+	let height = u32::from_be_bytes([data[20], data[21], data[22], data[23]]);
+
+	Some((width, height))
+}
+
+/// Get the dimensions of a GIF image from its logical screen descriptor.
+fn gif(data: &[u8]) -> Option<(u32, u32)> {
+	if data.len() < 10 || (&data[.. 6] != b"GIF87a" && &data[.. 6] != b"GIF89a") {
+		return None;
+	}
+
+	let width  = u16::from_le_bytes([data[6], data[7]]);
+	let height = u16::from_le_bytes([data[8], data[9]]);
+
+	Some((width as u32, height as u32))
+}
+
+/// Get the dimensions of a JPEG image from its start of frame segment.
+fn jpeg(data: &[u8]) -> Option<(u32, u32)> {
+	if data.len() < 4 || &data[.. 2] != b"\xFF\xD8" {
+		return None;
+	}
+
+	let mut offset = 2;
+
+	while offset + 4 <= data.len() {
+		if data[offset] != 0xFF {
+			return None;
+		}
+
+		let marker = data[offset + 1];
+
+		// Fill bytes and markers without a length.
+		if marker == 0xFF {
+			offset += 1;
+			continue;
+		}
+
+		if marker == 0x01 || (marker >= 0xD0 && marker <= 0xD7) {
+			offset += 2;
+			continue;
+		}
+
+		let length = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+
+		// Any SOF but DHT, JPG and DAC.
+		if marker >= 0xC0 && marker <= 0xCF && marker != 0xC4 && marker != 0xC8 && marker != 0xCC {
+			let frame = data.get(offset + 5 .. offset + 9)?;
+
+			let height = u16::from_be_bytes([frame[0], frame[1]]);
+			let width  = u16::from_be_bytes([frame[2], frame[3]]);
+
+			return Some((width as u32, height as u32));
+		}
+
+		offset += 2 + length;
+	}
+
+	None
+}
+
+/// Get the dimensions of a BMP image from its DIB header.
+fn bmp(data: &[u8]) -> Option<(u32, u32)> {
+	if data.len() < 26 || &data[.. 2] != b"BM" {
+		return None;
+	}
+
+	let size = u32::from_le_bytes([data[14], data[15], data[16], data[17]]);
+
+	// The OS/2 header uses 16 bit dimensions, the others signed 32 bit ones.
+	if size == 12 {
+		let width  = u16::from_le_bytes([data[18], data[19]]);
+		let height = u16::from_le_bytes([data[20], data[21]]);
+
+		Some((width as u32, height as u32))
+	}
+	else {
+		let width  = i32::from_le_bytes([data[18], data[19], data[20], data[21]]);
+		let height = i32::from_le_bytes([data[22], data[23], data[24], data[25]]);
+
+		Some((width.unsigned_abs(), height.unsigned_abs()))
+	}
+}
+
+/// Read the given range of a regular file, a `size` of 0 reads up to the end.
+///
+/// Only regular files outside of `/proc`, `/sys` and `/dev` are read, and
+/// never more than the memory limit.
+fn region(path: &str, from: usize, size: usize) -> Option<Vec<u8>> {
+	let path = fs::canonicalize(path).ok()?;
+
+	if ["/proc", "/sys", "/dev"].iter().any(|p| path.starts_with(Path::new(p))) {
+		return None;
+	}
+
+	// Check before opening, since opening a FIFO would block.
+	if !fs::metadata(&path).ok()?.is_file() {
+		return None;
+	}
+
+	let mut file     = File::open(&path).ok()?;
+	let     metadata = file.metadata().ok()?;
+
+	if !metadata.is_file() {
+		return None;
+	}
+
+	let length = metadata.len();
+	let start  = cmp::min(from as u64, length);
+	let end    = if size == 0 {
+		length
+	}
+	else {
+		cmp::min(start.saturating_add(size as u64), length)
+	};
+
+	if end - start > LIMIT as u64 {
+		return None;
+	}
+
+	let mut data = Vec::with_capacity((end - start) as usize);
+	file.seek(SeekFrom::Start(start)).ok()?;
+	file.take(end - start).read_to_end(&mut data).ok()?;
+
+	Some(data)
+}
+
+impl Graphics {
+	/// Create an empty image store.
+	pub fn new() -> Self {
+		Graphics {
+			images:  Default::default(),
+			size:    0,
+			serial:  0,
+			next:    1 << 31,
+			pending: None,
+
+			placements: Vec::new(),
+			alternate:  Vec::new(),
+		}
+	}
+
+	/// Get the id of the newest image with the given number.
+	pub fn number(&self, number: u32) -> Option<u32> {
+		self.images.iter()
+			.filter(|&(_, entry)| entry.number == number)
+			.max_by_key(|&(_, entry)| entry.serial)
+			.map(|(&id, _)| id)
+	}
+
+	/// Switch the placements between the primary and alternate screen.
+	pub fn swap(&mut self) {
+		mem::swap(&mut self.placements, &mut self.alternate);
+	}
+
+	/// Handle a transmission.
+	///
+	/// Returns `None` while waiting for more chunks, otherwise the control data
+	/// of the first chunk and the id of the stored image. Images can only be
+	/// read from files when `files` is enabled.
+	pub fn transmit(&mut self, control: Control, payload: &str, files: bool) -> Option<(Control, Result<u32, Error>)> {
+		// Following chunks only carry `m` and `q`, the rest comes from the first.
+		let (control, payload) = match self.pending.take() {
+			Some((first, mut data)) => {
+				data.push_str(payload);
+
+				(Control { more: control.more, ..first }, data)
+			}
+
+			None =>
+				(control, String::from(payload)),
+		};
+
+		if control.more {
+			if payload.len() / 4 * 3 > LIMIT {
+				return Some((control, Err(Error::TooBig)));
+			}
+
+			self.pending = Some((control, payload));
+			return None;
+		}
+
+		let result = self.load(&control, &payload, files).map(|image| {
+			// Queries only check the image can be loaded.
+			if control.action == b'q' {
+				return control.id;
+			}
+
+			let id = if control.id != 0 {
+				control.id
+			}
+			else {
+				self.identifier()
+			};
+
+			self.insert(id, control.number, image);
+			id
+		});
+
+		Some((control, result))
+	}
+
+	/// Decode the image from the payload.
+	fn load(&self, control: &Control, payload: &str, files: bool) -> Result<cairo::Image, Error> {
+		if control.compression.is_some() {
+			return Err(Error::Invalid("unsupported compression"));
+		}
+
+		let data = util::base64::decode(payload)
+			.ok_or(Error::Invalid("malformed payload"))?;
+
+		let data = match control.medium {
+			b'd' =>
+				data,
+
+			b'f' | b't' if files => {
+				let path = String::from_utf8(data)
+					.map_err(|_| Error::Invalid("malformed path"))?;
+
+				// The same error is returned for any failure, so the reply doesn't
+				// tell anything about the file.
+				let file = region(&path, control.from, control.size)
+					.ok_or(Error::Invalid("file not available"))?;
+
+				// Temporary files are removed once read.
+				if control.medium == b't' && path.contains("tty-graphics-protocol") && path.starts_with(&*env::temp_dir().to_string_lossy()) {
+					fs::remove_file(&path).ok();
+				}
+
+				file
+			}
+
+			_ =>
+				return Err(Error::Invalid("unsupported transmission medium")),
+		};
+
+		match control.format {
+			24 | 32 => {
+				let depth           = (control.format / 8) as usize;
+				let (width, height) = (control.width, control.height);
+
+				if width == 0 || height == 0 {
+					return Err(Error::Invalid("missing image dimensions"));
+				}
+
+				if width as u64 * height as u64 * 4 > LIMIT as u64 {
+					return Err(Error::TooBig);
+				}
+
+				if data.len() < (width * height) as usize * depth {
+					return Err(Error::NoData("insufficient image data"));
+				}
+
+				let mut image = cairo::Image::new(width, height);
+
+				for (i, px) in data.chunks(depth).take((width * height) as usize).enumerate() {
+					let alpha = if depth == 4 { px[3] } else { 255 };
+					let mul   = |c: u8| ((c as u32 * alpha as u32 + 127) / 255) as u8;
+
+					image.set(i as u32 % width, i as u32 / width,
+						&(mul(px[0]), mul(px[1]), mul(px[2]), alpha));
+				}
+
+				Ok(image)
+			}
+
+			100 => {
+				let (width, height) = png(&data)
+					.ok_or(Error::Invalid("malformed PNG data"))?;
+
+				// Check the size before decoding, the data is compressed.
+				if width as u64 * height as u64 * 4 > LIMIT as u64 {
+					return Err(Error::TooBig);
+				}
+
+				let decoded = read::from_memory::<Rgba, u8, _>(&data)
+					.map_err(|_| Error::Invalid("malformed PNG data"))?;
+
+				let mut image = cairo::Image::new(decoded.width(), decoded.height());
+
+				for y in 0 .. decoded.height() {
+					for x in 0 .. decoded.width() {
+						let px    = decoded.get(x, y);
+						let alpha = px.alpha;
+
+						image.set(x, y, &(
+							(px.red   * alpha * 255.0) as u8,
+							(px.green * alpha * 255.0) as u8,
+							(px.blue  * alpha * 255.0) as u8,
+							(alpha * 255.0) as u8));
+					}
+				}
+
+				Ok(image)
+			}
+
+			_ =>
+				Err(Error::Invalid("unsupported format")),
+		}
+	}
+
+	/// Get an unused image id.
+	fn identifier(&mut self) -> u32 {
+		while self.next == 0 || self.images.contains_key(&self.next) {
+			self.next = self.next.wrapping_add(1);
+		}
+
+		self.next
+	}
+
+	/// Store an image, replacing any image with the same id and evicting the
+	/// oldest ones when going over the memory limit.
+	fn insert(&mut self, id: u32, number: u32, image: cairo::Image) {
+		self.remove(id);
+		self.serial += 1;
+		self.size   += image.as_ref().len();
+		self.images.insert(id, Entry {
+			number: number,
+			serial: self.serial,
+			image:  image,
+		});
+
+		while self.size > LIMIT {
+			let oldest = self.images.iter()
+				.filter(|&(&key, _)| key != id)
+				.min_by_key(|&(_, entry)| entry.serial)
+				.map(|(&key, _)| key);
+
+			if let Some(oldest) = oldest {
+				self.remove(oldest);
+			}
+			else {
+				break;
+			}
+		}
+	}
+
+	/// Remove an image and all its placements.
+	fn remove(&mut self, id: u32) {
+		if let Some(entry) = self.images.remove(&id) {
+			self.size -= entry.image.as_ref().len();
+		}
+
+		self.placements.retain(|p| p.image != id);
+		self.alternate.retain(|p| p.image != id);
+	}
+
+	/// Place an image at the given cell, returns the columns and rows covered.
+	pub fn place(&mut self, id: u32, control: &Control, (x, y): (u32, u32), cell: (u32, u32)) -> Result<(u32, u32), Error> {
+		let (width, height) = {
+			let entry = self.images.get(&id).ok_or(Error::NotFound("image not found"))?;
+			(entry.image.width(), entry.image.height())
+		};
+
+		let sx = cmp::min(control.source.0, width);
+		let sy = cmp::min(control.source.1, height);
+		let sw = if control.source.2 == 0 { width - sx } else { cmp::min(control.source.2, width - sx) };
+		let sh = if control.source.3 == 0 { height - sy } else { cmp::min(control.source.3, height - sy) };
+
+		if sw == 0 || sh == 0 {
+			return Err(Error::Invalid("empty source rectangle"));
+		}
+
+		// Scale to the requested cells, keeping the aspect ratio if only one is
+		// given.
+		let size = match control.cells {
+			(0, 0) =>
+				(sw as f64, sh as f64),
+
+			(c, 0) => {
+				let w = (c * cell.0) as f64;
+				(w, sh as f64 * w / sw as f64)
+			}
+
+			(0, r) => {
+				let h = (r * cell.1) as f64;
+				(sw as f64 * h / sh as f64, h)
+			}
+
+			(c, r) =>
+				((c * cell.0) as f64, (r * cell.1) as f64),
+		};
+
+		let offset = (
+			cmp::min(control.offset.0, cell.0.saturating_sub(1)),
+			cmp::min(control.offset.1, cell.1.saturating_sub(1)));
+
+		let cells = (
+			if control.cells.0 != 0 { control.cells.0 } else { ((offset.0 as f64 + size.0) / cell.0 as f64).ceil() as u32 },
+			if control.cells.1 != 0 { control.cells.1 } else { ((offset.1 as f64 + size.1) / cell.1 as f64).ceil() as u32 });
+
+		// A placement with the same id replaces the previous one.
+		if control.placement != 0 {
+			self.placements.retain(|p| !(p.image == id && p.id == control.placement));
+		}
+
+		self.placements.push(Placement {
+			image:  id,
+			id:     control.placement,
+			x:      x,
+			y:      y as i64,
+			offset: offset,
+			source: (sx, sy, sw, sh),
+			size:   size,
+			cells:  cells,
+			z:      control.z,
+		});
+
+		// Keep the placements in drawing order.
+		self.placements.sort_by_key(|p| p.z);
+
+		Ok(cells)
+	}
+
+	/// Delete the placements selected by the control data, the uppercase
+	/// variants also free the images left without placements.
+	pub fn delete(&mut self, control: &Control, (x, y): (u32, u32)) {
+		let free   = control.delete.is_ascii_uppercase();
+		let target = match control.delete.to_ascii_lowercase() {
+			b'i' => Some(control.id),
+			b'n' => self.number(control.number),
+			_    => None,
+		};
+
+		let (cx, cy) = (control.source.0.saturating_sub(1), control.source.1 as i64 - 1);
+		let matches  = |p: &Placement| match control.delete.to_ascii_lowercase() {
+			b'a' => p.y + p.cells.1 as i64 > 0,
+			b'i' | b'n' => Some(p.image) == target && (control.placement == 0 || p.id == control.placement),
+			b'c' => p.contains(x, y as i64),
+			b'p' => p.contains(cx, cy),
+			b'q' => p.contains(cx, cy) && p.z == control.z,
+			b'x' => p.column(cx),
+			b'y' => p.row(cy),
+			b'z' => p.z == control.z,
+			b'r' => p.image >= control.source.0 && p.image <= control.source.1,
+			_    => false,
+		};
+
+		let mut removed = target.into_iter().collect::<Vec<u32>>();
+		for placement in self.placements.iter().filter(|p| matches(p)) {
+			removed.push(placement.image);
+		}
+
+		self.placements.retain(|p| !matches(p));
+
+		if free {
+			for id in removed {
+				if !self.placements.iter().chain(self.alternate.iter()).any(|p| p.image == id) {
+					self.remove(id);
+				}
+			}
+		}
+	}
+
+	/// Delete the placements on the screen.
+	pub fn clear(&mut self) -> bool {
+		let before = self.placements.len();
+		self.placements.retain(|p| p.y + p.cells.1 as i64 <= 0);
+
+		before != self.placements.len()
+	}
+
+	/// Scroll the placements up by `n`, within the given region or into the
+	/// history, returns whether any placement was affected.
+	pub fn up(&mut self, n: u32, region: Option<(u32, u32)>, history: usize) -> bool {
+		let n = n as i64;
+
+		if let Some((top, bottom)) = region {
+			let (top, bottom) = (top as i64, bottom as i64);
+			let inside        = |p: &Placement| p.y >= top && p.y <= bottom;
+
+			if !self.placements.iter().any(|p| inside(p)) {
+				return false;
+			}
+
+			self.placements.retain(|p| !inside(p) || p.y - n >= top);
+			for placement in self.placements.iter_mut().filter(|p| inside(p)) {
+				placement.y -= n;
+			}
+		}
+		else {
+			if self.placements.is_empty() {
+				return false;
+			}
+
+			for placement in &mut self.placements {
+				placement.y -= n;
+			}
+
+			self.placements.retain(|p| p.y + p.cells.1 as i64 > -(history as i64));
+		}
+
+		true
+	}
+
+	/// Scroll the placements down by `n` within the given region, returns
+	/// whether any placement was affected.
+	pub fn down(&mut self, n: u32, (top, bottom): (u32, u32)) -> bool {
+		let (n, top, bottom) = (n as i64, top as i64, bottom as i64);
+		let inside           = |p: &Placement| p.y >= top && p.y <= bottom;
+
+		if !self.placements.iter().any(|p| inside(p)) {
+			return false;
+		}
+
+		self.placements.retain(|p| !inside(p) || p.y + n <= bottom);
+		for placement in self.placements.iter_mut().filter(|p| inside(p)) {
+			placement.y += n;
+		}
+
+		true
+	}
+
+	/// Get the placements visible on a view of `rows` rows, with the placement
+	/// rows shifted by `offset`.
+	pub fn visible(&self, offset: i64, rows: u32) -> Vec<Visible> {
+		self.placements.iter().filter_map(|p| {
+			let y = p.y + offset;
+
+			if y >= rows as i64 || y + p.cells.1 as i64 <= 0 {
+				return None;
+			}
+
+			self.images.get(&p.image).map(|entry| Visible {
+				image:  &entry.image,
+				x:      p.x,
+				y:      y,
+				offset: p.offset,
+				source: p.source,
+				size:   p.size,
+				z:      p.z,
+				limit:  rows,
+			})
+		}).collect()
+	}
+}
@@ -21,7 +21,7 @@ use std::collections::VecDeque;
 
 use itertools::Itertools;
 use crate::util::clamp;
-use crate::terminal::{Cell, Row, Free};
+use crate::terminal::{Cell, Row, Mark, Size, Free};
 
 #[derive(Debug)]
 pub struct Grid {
@@ -106,6 +106,7 @@ impl Grid {
 
 					let mut unwrapped = Vec::new();
 					let     before    = wrapped.len();
+					let mut marks     = wrapped.iter_mut().rev().flat_map(|r| r.marks.drain(..)).collect::<Vec<_>>();
 
 					// Remove any empty leftover before trying to unwrap the row.
 					{
@@ -123,7 +124,7 @@ impl Grid {
 					// Create new rows with the cells and mark as wrapped if they do wrap
 					// again.
 					for (j, cells) in chunks.into_iter().enumerate() {
-						unwrapped.push(Row { inner: cells.collect(), wrapped: j != 0 });
+						unwrapped.push(Row { inner: cells.collect(), wrapped: j != 0, marks: mem::replace(&mut marks, Vec::new()), size: Size::Single });
 					}
 
 					// Extend any missing cells from the last row.
@@ -156,12 +157,13 @@ impl Grid {
 
 					if row.len() != cols as usize {
 						let mut wrapped = Vec::new();
+						let mut marks   = row.marks;
 						let     chunks  = row.inner.into_iter().chunks(cols as usize);
 
 						// Create new rows with the cells and mark as wrapped if they do
 						// wrap.
 						for (j, cells) in chunks.into_iter().enumerate() {
-							wrapped.push(Row { inner: cells.collect(), wrapped: j != 0 });
+							wrapped.push(Row { inner: cells.collect(), wrapped: j != 0, marks: mem::replace(&mut marks, Vec::new()), size: Size::Single });
 						}
 
 						// Extend any missing cells from the last row.
@@ -257,9 +259,30 @@ impl Grid {
 		}
 	}
 
-	/// Scroll the view up by `n`, optionally within the given region.
-	pub fn up(&mut self, n: u32, region: Option<(u32, u32)>) {
-		if let Some(region) = region {
+	/// Scroll the view up by `n`, optionally within the given region and
+	/// columns.
+	pub fn up(&mut self, n: u32, region: Option<(u32, u32)>, columns: Option<(u32, u32)>) {
+		if let (Some(region), Some(columns)) = (region, columns) {
+			let n = clamp(n as u32, 0, region.1 - region.0 + 1);
+
+			for y in region.0 ..= region.1 {
+				for x in columns.0 ..= columns.1 {
+					let cell = if y + n <= region.1 {
+						mem::replace(&mut self.view[(y + n) as usize][x as usize], self.free.cell())
+					}
+					else {
+						self.free.cell()
+					};
+
+					self.view[y as usize][x as usize] = cell;
+				}
+			}
+
+			for y in region.0 ..= region.1 {
+				self.clean_columns(y, columns);
+			}
+		}
+		else if let Some(region) = region {
 			let y      = region.0;
 			let n      = clamp(n as u32, 0, region.1 - y + 1);
 			let offset = self.rows - (region.1 + 1);
@@ -283,9 +306,29 @@ impl Grid {
 		self.clean_history();
 	}
 
-	/// Scroll the view down by `n`, optionally within the region.
-	pub fn down(&mut self, n: u32, region: Option<(u32, u32)>) {
-		if let Some(region) = region {
+	/// Scroll the view down by `n`, optionally within the region and columns.
+	pub fn down(&mut self, n: u32, region: Option<(u32, u32)>, columns: Option<(u32, u32)>) {
+		if let (Some(region), Some(columns)) = (region, columns) {
+			let n = clamp(n as u32, 0, region.1 - region.0 + 1);
+
+			for y in (region.0 ..= region.1).rev() {
+				for x in columns.0 ..= columns.1 {
+					let cell = if y >= region.0 + n {
+						mem::replace(&mut self.view[(y - n) as usize][x as usize], self.free.cell())
+					}
+					else {
+						self.free.cell()
+					};
+
+					self.view[y as usize][x as usize] = cell;
+				}
+			}
+
+			for y in region.0 ..= region.1 {
+				self.clean_columns(y, columns);
+			}
+		}
+		else if let Some(region) = region {
 			let y = region.0;
 			let n = clamp(n as u32, 0, region.1 - y + 1);
 
@@ -308,44 +351,58 @@ impl Grid {
 		self.clean_history();
 	}
 
-	/// Delete `n` cells starting from the given origin.
-	pub fn delete(&mut self, x: u32, y: u32, n: u32) {
-		let n   = clamp(n, 0, self.cols - x);
-		let row = &mut self.view[y as usize];
+	/// Delete `n` cells starting from the given origin, up to the given right
+	/// column.
+	pub fn delete(&mut self, x: u32, y: u32, n: u32, right: u32) {
+		let limit = clamp(right + 1, x + 1, self.cols);
+		let n     = clamp(n, 0, limit - x);
 
-		// The row may contain references, account for them.
-		let mut end = x;
-		for _ in 0 .. n {
-			end += row[end as usize].width();
+		{
+			let row = &mut self.view[y as usize];
 
-			if end >= self.cols {
-				end = self.cols - 1;
-				break;
+			// The row may contain references, account for them.
+			let mut end = x;
+			for _ in 0 .. n {
+				end += row[end as usize].width();
+
+				if end >= limit {
+					end = limit - 1;
+					break;
+				}
 			}
-		}
 
-		// Drain the cells and insert empty ones at the end.
-		row.drain(x as usize .. end as usize);
-		row.append(&mut vec_deque![self.free.cell(); (end - x) as usize]);
-	}
+			// Drain the cells and insert empty ones at the right edge.
+			row.drain(x as usize .. end as usize);
 
-	/// Insert `n` empty cells starting from the given origin.
-	pub fn insert(&mut self, x: u32, y: u32, n: u32) {
-		let n   = clamp(n as u32, 0, self.cols);
-		let row = &mut self.view[y as usize];
+			for _ in x .. end {
+				row.insert((limit - (end - x)) as usize, self.free.cell());
+			}
+		}
 
-		for _ in x .. x + n {
-			row.insert(x as usize, self.free.cell());
+		if limit < self.cols {
+			self.clean_references(limit, y);
 		}
+	}
 
-		row.drain(self.cols as usize ..);
+	/// Insert `n` empty cells starting from the given origin, up to the given
+	/// right column.
+	pub fn insert(&mut self, x: u32, y: u32, n: u32, right: u32) {
+		let limit = clamp(right + 1, x + 1, self.cols);
+		let n     = clamp(n as u32, 0, limit - x);
 
-		// Check if the last occupied cell width corresponds to the number of
-		// references.
 		{
+			let row = &mut self.view[y as usize];
+
+			for _ in x .. x + n {
+				row.remove((limit - 1) as usize);
+				row.insert(x as usize, self.free.cell());
+			}
+
+			// Check if the last occupied cell width corresponds to the number of
+			// references.
 			let mut width = 0;
 
-			for x in (0 .. self.cols).rev() {
+			for x in (0 .. limit).rev() {
 				width += 1;
 
 				if !row[x as usize].is_reference()  {
@@ -353,20 +410,77 @@ impl Grid {
 				}
 			}
 
-			let start = self.cols - width;
+			let start = limit - width;
 
 			if width != row[start as usize].width() {
-				for x in start .. self.cols {
+				for x in start .. limit {
 					row[x as usize].make_empty(self.free.style());
 				}
 			}
 		}
+
+		if limit < self.cols {
+			self.clean_references(limit, y);
+		}
+	}
+
+	/// Clean wide characters split by the edges of the given columns.
+	fn clean_columns(&mut self, y: u32, (left, right): (u32, u32)) {
+		{
+			let row = &mut self.view[y as usize];
+
+			// Wide characters before the left edge lost their references.
+			if left > 0 {
+				let mut start = left - 1;
+
+				while start > 0 && row[start as usize].is_reference() {
+					start -= 1;
+				}
+
+				if !row[start as usize].is_reference() && row[start as usize].width() > left - start {
+					for x in start .. left {
+						row[x as usize].make_empty(self.free.style());
+					}
+				}
+			}
+
+			for x in left ..= right {
+				if !row[x as usize].is_reference() {
+					break;
+				}
+
+				row[x as usize].make_empty(self.free.style());
+			}
+
+			if right + 1 < self.cols && !row[right as usize].is_reference() && row[right as usize].width() > 1 {
+				row[right as usize].make_empty(self.free.style());
+			}
+		}
+
+		if right + 1 < self.cols {
+			self.clean_references(right + 1, y);
+		}
 	}
 
 	/// Mark a row as wrapped.
 	pub fn wrapped(&mut self, y: u32, value: bool) {
 		self.view[y as usize].wrapped = value;
 	}
+
+	/// Add a semantic mark to a row.
+	pub fn mark(&mut self, y: u32, mark: Mark) {
+		self.view[y as usize].marks.push(mark);
+	}
+
+	/// Remove the semantic marks from a row.
+	pub fn unmark(&mut self, y: u32) {
+		self.view[y as usize].marks.clear();
+	}
+
+	/// Change the line size of a row.
+	pub fn size(&mut self, y: u32, size: Size) {
+		self.view[y as usize].size = size;
+	}
 }
 
 impl Index<u32> for Grid {
@@ -0,0 +1,274 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+
+use picto::read;
+use picto::color::Rgba;
+use crate::util;
+use crate::sys::cairo;
+use crate::terminal::graphics;
+
+/// A file sent through the iTerm2 inline image protocol.
+#[derive(Debug)]
+pub struct Inline {
+	name:     Option<String>,
+	width:    Dimension,
+	height:   Dimension,
+	preserve: bool,
+	inline:   bool,
+	data:     Vec<u8>,
+}
+
+/// A requested image dimension.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Dimension {
+	Auto,
+	Cells(u32),
+	Pixels(u32),
+	Percent(u32),
+}
+
+impl Dimension {
+	fn parse(value: &str) -> Self {
+		if value == "auto" {
+			Dimension::Auto
+		}
+		else if value.ends_with("px") {
+			value[.. value.len() - 2].parse().map(Dimension::Pixels).unwrap_or(Dimension::Auto)
+		}
+		else if value.ends_with('%') {
+			value[.. value.len() - 1].parse().map(Dimension::Percent).unwrap_or(Dimension::Auto)
+		}
+		else {
+			value.parse().map(Dimension::Cells).unwrap_or(Dimension::Auto)
+		}
+	}
+
+	/// Get the size in pixels given the cell size and the total size.
+	fn pixels(&self, cell: u32, total: u64) -> Option<u64> {
+		match *self {
+			Dimension::Auto       => None,
+			Dimension::Cells(n)   => Some(n as u64 * cell as u64),
+			Dimension::Pixels(n)  => Some(n as u64),
+			Dimension::Percent(n) => Some(total * cmp::min(n, 100) as u64 / 100),
+		}
+	}
+}
+
+impl Inline {
+	/// Parse the arguments and content of a `File=` command.
+	pub fn parse(string: &str) -> Option<Self> {
+		let mut parts = string.splitn(2, ':');
+		let     args  = parts.next()?;
+		let     data  = util::base64::decode(parts.next()?)?;
+
+		let mut value = Inline {
+			name:     None,
+			width:    Dimension::Auto,
+			height:   Dimension::Auto,
+			preserve: true,
+			inline:   false,
+			data:     data,
+		};
+
+		for arg in args.split(';') {
+			let mut arg = arg.splitn(2, '=');
+
+			match (arg.next(), arg.next()) {
+				(Some("name"), Some(name)) =>
+					value.name = util::base64::decode(name).and_then(|n| String::from_utf8(n).ok()),
+
+				(Some("width"), Some(width)) =>
+					value.width = Dimension::parse(width),
+
+				(Some("height"), Some(height)) =>
+					value.height = Dimension::parse(height),
+
+				(Some("preserveAspectRatio"), Some(preserve)) =>
+					value.preserve = preserve != "0",
+
+				(Some("inline"), Some(inline)) =>
+					value.inline = inline == "1",
+
+				_ => ()
+			}
+		}
+
+		Some(value)
+	}
+
+	/// The name of the file, if any.
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_ref().map(AsRef::as_ref)
+	}
+
+	/// Whether the file should be displayed.
+	pub fn is_inline(&self) -> bool {
+		self.inline
+	}
+
+	/// Get the file content.
+	pub fn into_inner(self) -> Vec<u8> {
+		self.data
+	}
+
+	/// Decode the image and split it in cells, the image is scaled down to fit
+	/// within the given amount of columns and rows.
+	pub fn cells(&self, cell: (u32, u32), limit: (u32, u32)) -> Option<Vec<Vec<cairo::Image>>> {
+		// Check the size before decoding, formats whose header can't be checked
+		// are not decoded at all.
+		let (width, height) = graphics::dimensions(&self.data)?;
+
+		if width as u64 * height as u64 * 4 > graphics::LIMIT as u64 {
+			return None;
+		}
+
+		let image = read::from_memory::<Rgba, u8, _>(&self.data).ok()?;
+		let (iw, ih) = (image.width() as u64, image.height() as u64);
+
+		if iw == 0 || ih == 0 {
+			return None;
+		}
+
+		let (max_width, max_height) = (limit.0 as u64 * cell.0 as u64, limit.1 as u64 * cell.1 as u64);
+		let (mut width, mut height) = match (self.width.pixels(cell.0, max_width), self.height.pixels(cell.1, max_height)) {
+			(None, None) =>
+				(iw, ih),
+
+			(Some(w), None) =>
+				(w, ih.checked_mul(w)? / iw),
+
+			(None, Some(h)) =>
+				(iw.checked_mul(h)? / ih, h),
+
+			(Some(w), Some(h)) if self.preserve => {
+				let scale = (w as f64 / iw as f64).min(h as f64 / ih as f64);
+				((iw as f64 * scale) as u64, (ih as f64 * scale) as u64)
+			}
+
+			(Some(w), Some(h)) =>
+				(w, h),
+		};
+
+		// Scale down to fit within the limit.
+		if width > max_width {
+			if self.preserve {
+				height = height.checked_mul(max_width)? / width;
+			}
+
+			width = max_width;
+		}
+
+		if height > max_height {
+			if self.preserve {
+				width = width.checked_mul(max_height)? / height;
+			}
+
+			height = max_height;
+		}
+
+		if width == 0 || height == 0 {
+			return None;
+		}
+
+		// Both fit in the limit now.
+		let (iw, ih)        = (iw as u32, ih as u32);
+		let (width, height) = (width as u32, height as u32);
+
+		let columns = (width + cell.0 - 1) / cell.0;
+		let rows    = (height + cell.1 - 1) / cell.1;
+
+		Some((0 .. rows).map(|row| (0 .. columns).map(|column| {
+			let mut buffer = cairo::Image::new(cell.0, cell.1);
+
+			for y in 0 .. cell.1 {
+				for x in 0 .. cell.0 {
+					let (x, y) = (column * cell.0 + x, row * cell.1 + y);
+
+					if x >= width || y >= height {
+						continue;
+					}
+
+					let px    = image.get((x as u64 * iw as u64 / width as u64) as u32, (y as u64 * ih as u64 / height as u64) as u32);
+					let alpha = px.alpha;
+
+					buffer.set(x - column * cell.0, y - row * cell.1, &(
+						(px.red   * alpha * 255.0) as u8,
+						(px.green * alpha * 255.0) as u8,
+						(px.blue  * alpha * 255.0) as u8,
+						(alpha * 255.0) as u8));
+				}
+			}
+
+			buffer
+		}).collect()).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_defaults() {
+		let file = Inline::parse(":Zm9v").unwrap();
+
+		assert_eq!(file.name(), None);
+		assert_eq!(file.width, Dimension::Auto);
+		assert_eq!(file.height, Dimension::Auto);
+		assert!(file.preserve);
+		assert!(!file.is_inline());
+		assert_eq!(file.into_inner(), b"foo");
+	}
+
+	#[test]
+	fn parse_arguments() {
+		let file = Inline::parse("name=dGVzdC5wbmc=;width=10;height=50%;preserveAspectRatio=0;inline=1:Zm9v").unwrap();
+
+		assert_eq!(file.name(), Some("test.png"));
+		assert_eq!(file.width, Dimension::Cells(10));
+		assert_eq!(file.height, Dimension::Percent(50));
+		assert!(!file.preserve);
+		assert!(file.is_inline());
+	}
+
+	#[test]
+	fn parse_dimensions() {
+		assert_eq!(Dimension::parse("auto"), Dimension::Auto);
+		assert_eq!(Dimension::parse("12"), Dimension::Cells(12));
+		assert_eq!(Dimension::parse("120px"), Dimension::Pixels(120));
+		assert_eq!(Dimension::parse("75%"), Dimension::Percent(75));
+		assert_eq!(Dimension::parse("wide"), Dimension::Auto);
+		assert_eq!(Dimension::parse("-1px"), Dimension::Auto);
+	}
+
+	#[test]
+	fn parse_invalid() {
+		assert!(Inline::parse("inline=1").is_none());
+		assert!(Inline::parse("inline=1:!!").is_none());
+	}
+
+	#[test]
+	fn pixels() {
+		assert_eq!(Dimension::Auto.pixels(8, 800), None);
+		assert_eq!(Dimension::Cells(10).pixels(8, 800), Some(80));
+		assert_eq!(Dimension::Pixels(100).pixels(8, 800), Some(100));
+		assert_eq!(Dimension::Percent(50).pixels(8, 800), Some(400));
+		assert_eq!(Dimension::Percent(200).pixels(8, 800), Some(800));
+	}
+}
@@ -16,6 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use control;
+use crate::terminal::sequence::{self, Parsed};
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Input<'a> {
@@ -64,7 +65,7 @@ pub fn parse(i: &[u8]) -> Input {
 
 	let mut rest = &i[length..];
 
-	while !rest.is_empty() && control::parse(rest).is_err() {
+	while !rest.is_empty() && control::parse(rest).is_err() && sequence::parse(rest) == Parsed::Error {
 		let w = WIDTH[rest[0] as usize] as usize;
 
 		if w > 1 {
@@ -0,0 +1,402 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+use crate::platform::key::{self, Key, Value, Button, Keypad};
+
+bitflags! {
+	/// Progressive enhancements of the kitty keyboard protocol.
+	pub struct Flags: u8 {
+		const DISAMBIGUATE = 1 << 0;
+		const EVENTS       = 1 << 1;
+		const ALTERNATES   = 1 << 2;
+		const ALL          = 1 << 3;
+		const TEXT         = 1 << 4;
+	}
+}
+
+/// The maximum depth of the flags stack.
+const DEPTH: usize = 16;
+
+/// Keeps the stack of enhancement flags for each screen, and the xterm
+/// modifyOtherKeys level.
+#[derive(Clone, Default, Debug)]
+pub struct Keyboard {
+	primary:   Vec<Flags>,
+	alternate: Vec<Flags>,
+	current:   bool,
+	others:    u8,
+}
+
+/// Get the xterm modifier parameter for the key, 1 means no modifiers.
+pub fn modifiers(key: &Key) -> u32 {
+	let mut result = 0;
+
+	for &(modifier, value) in &[(key::SHIFT, 1), (key::ALT, 2), (key::CTRL, 4), (key::LOGO, 8)] {
+		if key.modifier().contains(modifier) {
+			result |= value;
+		}
+	}
+
+	result + 1
+}
+
+/// Get the xterm number, final byte and implied modifiers of a function or
+/// cursor key.
+pub fn function(value: &Value) -> Option<(u32, u8, u32)> {
+	Some(match *value {
+		Value::Button(Button::Up)       | Value::Keypad(Keypad::Up)       => (1, b'A', 0),
+		Value::Button(Button::Down)     | Value::Keypad(Keypad::Down)     => (1, b'B', 0),
+		Value::Button(Button::Right)    | Value::Keypad(Keypad::Right)    => (1, b'C', 0),
+		Value::Button(Button::Left)     | Value::Keypad(Keypad::Left)     => (1, b'D', 0),
+		Value::Button(Button::Home)     | Value::Keypad(Keypad::Home)     => (1, b'H', 0),
+		Value::Button(Button::End)      | Value::Keypad(Keypad::End)      => (1, b'F', 0),
+		Value::Button(Button::Insert)   | Value::Keypad(Keypad::Insert)   => (2, b'~', 0),
+		Value::Button(Button::PageUp)   | Value::Keypad(Keypad::PageUp)   => (5, b'~', 0),
+		Value::Button(Button::PageDown) | Value::Keypad(Keypad::PageDown) => (6, b'~', 0),
+		Value::Button(Button::Delete)                                     => (3, b'~', 0),
+		Value::Keypad(Keypad::Begin)                                      => (1, b'E', 0),
+
+		// F13 to F24 are shifted and F25 to F36 are controlled F1 to F12.
+		Value::Button(Button::F(n)) if n >= 1 && n <= 36 => {
+			let implied = match (n - 1) / 12 {
+				0 => 0,
+				1 => 1,
+				_ => 4,
+			};
+
+			let (number, end) = match (n - 1) % 12 + 1 {
+				1  => (1, b'P'),
+				2  => (1, b'Q'),
+				3  => (1, b'R'),
+				4  => (1, b'S'),
+				5  => (15, b'~'),
+				11 => (23, b'~'),
+				12 => (24, b'~'),
+				n  => (n as u32 + 11, b'~'),
+			};
+
+			(number, end, implied)
+		}
+
+		_ =>
+			return None
+	})
+}
+
+impl Keyboard {
+	/// Switch between the stacks of the primary and alternate screen.
+	pub fn screen(&mut self, alternate: bool) {
+		self.current = alternate;
+	}
+
+	fn stack(&mut self) -> &mut Vec<Flags> {
+		if self.current {
+			&mut self.alternate
+		}
+		else {
+			&mut self.primary
+		}
+	}
+
+	/// Get the current flags.
+	pub fn flags(&self) -> Flags {
+		let stack = if self.current { &self.alternate } else { &self.primary };
+		stack.last().cloned().unwrap_or(Flags::empty())
+	}
+
+	/// Push new flags, dropping the oldest ones when the stack is full.
+	pub fn push(&mut self, flags: Flags) {
+		let stack = self.stack();
+
+		if stack.len() >= DEPTH {
+			stack.remove(0);
+		}
+
+		stack.push(flags);
+	}
+
+	/// Pop `n` entries from the stack.
+	pub fn pop(&mut self, n: u32) {
+		let stack  = self.stack();
+		let length = stack.len().saturating_sub(n as usize);
+
+		stack.truncate(length);
+	}
+
+	/// Change the current flags, 1 replaces them, 2 sets the given ones and 3
+	/// resets the given ones.
+	pub fn set(&mut self, flags: Flags, mode: u32) {
+		let stack = self.stack();
+
+		if stack.is_empty() {
+			stack.push(Flags::empty());
+		}
+
+		let current = stack.last_mut().unwrap();
+
+		match mode {
+			2 => current.insert(flags),
+			3 => current.remove(flags),
+			_ => *current = flags,
+		}
+	}
+
+	/// Get the modifyOtherKeys level.
+	pub fn others(&self) -> u8 {
+		self.others
+	}
+
+	/// Change the modifyOtherKeys level.
+	pub fn set_others(&mut self, level: u8) {
+		self.others = level;
+	}
+
+	/// Encode the key with the current flags, returns `None` when the legacy
+	/// encoding should be used.
+	pub fn encode(&self, key: &Key) -> Option<Vec<u8>> {
+		let flags = self.flags();
+
+		if flags.is_empty() {
+			return self.other(key);
+		}
+
+		let mut modifiers = modifiers(key) - 1;
+
+		let event = match key.kind() {
+			key::Kind::Press   => 1,
+			key::Kind::Repeat  => 2,
+			key::Kind::Release => 3,
+		};
+
+		if event == 3 && !flags.contains(EVENTS) {
+			return None;
+		}
+
+		let (code, end) = match *key.value() {
+			Value::Char(ref string) => {
+				let mut chars = string.chars();
+				let     ch    = chars.next()?;
+
+				// Composed text is always sent as is.
+				if chars.next().is_some() {
+					return None;
+				}
+
+				// Text is sent as is unless it's ambiguous.
+				if !flags.contains(ALL) && modifiers & !1 == 0 {
+					if event == 3 {
+						return Some(Vec::new());
+					}
+
+					return None;
+				}
+
+				// The code is the key without modifiers, so Ctrl+Space and shifted
+				// symbols report the key they're on.
+				let base = key.base().unwrap_or_else(|| ch.to_lowercase().next().unwrap_or(ch));
+
+				(base as u32, b'u')
+			}
+
+			Value::Button(Button::Enter) |
+			Value::Button(Button::Tab) |
+			Value::Button(Button::Backspace) if !flags.contains(ALL) && modifiers == 0 => {
+				if event == 3 {
+					return Some(Vec::new());
+				}
+
+				return None;
+			}
+
+			Value::Button(button) => match button {
+				Button::Escape    => (27, b'u'),
+				Button::Enter     => (13, b'u'),
+				Button::Tab       => (9, b'u'),
+				Button::Backspace => (127, b'u'),
+				Button::Insert    => (2, b'~'),
+				Button::Delete    => (3, b'~'),
+				Button::PageUp    => (5, b'~'),
+				Button::PageDown  => (6, b'~'),
+				Button::Up        => (1, b'A'),
+				Button::Down      => (1, b'B'),
+				Button::Right     => (1, b'C'),
+				Button::Left      => (1, b'D'),
+				Button::Home      => (1, b'H'),
+				Button::End       => (1, b'F'),
+				Button::Menu      => (57363, b'u'),
+				Button::F(1)      => (1, b'P'),
+				Button::F(2)      => (1, b'Q'),
+				Button::F(3)      => (13, b'~'),
+				Button::F(4)      => (1, b'S'),
+				Button::F(5)      => (15, b'~'),
+				Button::F(n @ 6 ..= 10)  => (n as u32 + 11, b'~'),
+				Button::F(n @ 11 ..= 12) => (n as u32 + 12, b'~'),
+				Button::F(n)             => (57376 + n.saturating_sub(13) as u32, b'u'),
+			},
+
+			Value::Keypad(keypad) => (match keypad {
+				Keypad::Number(n) => 57399 + n as u32,
+				Keypad::Decimal   => 57409,
+				Keypad::Divide    => 57410,
+				Keypad::Multiply  => 57411,
+				Keypad::Subtract  => 57412,
+				Keypad::Add       => 57413,
+				Keypad::Enter     => 57414,
+				Keypad::Left      => 57417,
+				Keypad::Right     => 57418,
+				Keypad::Up        => 57419,
+				Keypad::Down      => 57420,
+				Keypad::PageUp    => 57421,
+				Keypad::PageDown  => 57422,
+				Keypad::Home      => 57423,
+				Keypad::End       => 57424,
+				Keypad::Insert    => 57425,
+				Keypad::Begin     => 57427,
+			}, b'u'),
+		};
+
+		// Locks are only reported when every key is.
+		if flags.contains(ALL) {
+			if key.lock().contains(key::CAPS) {
+				modifiers |= 64;
+			}
+
+			if key.lock().contains(key::NUM) {
+				modifiers |= 128;
+			}
+		}
+
+		// The shifted key, if it differs from the unshifted one.
+		let shifted = match *key.value() {
+			Value::Char(ref string) if flags.contains(ALTERNATES) && key.modifier().contains(key::SHIFT) =>
+				string.chars().next().map(|c| c as u32).filter(|&c| c != code),
+
+			_ =>
+				None
+		};
+
+		// The text the key produces.
+		let text = match *key.value() {
+			Value::Char(ref string) if flags.contains(ALL | TEXT) && event != 3 && modifiers & !(1 | 64 | 128) == 0 =>
+				Some(string.chars().map(|c| (c as u32).to_string()).collect::<Vec<_>>().join(":")),
+
+			_ =>
+				None
+		};
+
+		let reported   = flags.contains(EVENTS) && event != 1;
+		let parameters = modifiers != 0 || reported || text.is_some();
+		let mut result = String::from("\x1B[");
+
+		if code != 1 || end == b'u' || parameters || shifted.is_some() {
+			result.push_str(&code.to_string());
+		}
+
+		if let Some(shifted) = shifted {
+			result.push_str(&format!(":{}", shifted));
+		}
+
+		if parameters {
+			result.push_str(&format!(";{}", modifiers + 1));
+
+			if reported {
+				result.push_str(&format!(":{}", event));
+			}
+		}
+
+		if let Some(text) = text {
+			result.push_str(&format!(";{}", text));
+		}
+
+		result.push(end as char);
+		Some(result.into_bytes())
+	}
+
+	/// Encode the key following modifyOtherKeys, returns `None` when the legacy
+	/// encoding should be used.
+	fn other(&self, key: &Key) -> Option<Vec<u8>> {
+		if self.others == 0 || key.kind() == key::Kind::Release {
+			return None;
+		}
+
+		let modifier = key.modifier();
+		let code     = match *key.value() {
+			Value::Char(ref string) => {
+				let mut chars = string.chars();
+				let     ch    = chars.next()?;
+
+				if chars.next().is_some() {
+					return None;
+				}
+
+				// Shifted characters are just text.
+				if (modifier - key::SHIFT).is_empty() {
+					return None;
+				}
+
+				// The first level only changes combinations that can't be told apart
+				// otherwise.
+				if self.others == 1 {
+					let known = ch.is_ascii_lowercase() || ch == '@';
+
+					if !modifier.contains(key::CTRL) || (known && !modifier.contains(key::SHIFT)) {
+						return None;
+					}
+				}
+
+				ch as u32
+			}
+
+			Value::Button(Button::Enter)     => 13,
+			Value::Button(Button::Tab)       => 9,
+			Value::Button(Button::Backspace) => 127,
+			Value::Button(Button::Escape)    => 27,
+
+			_ =>
+				return None
+		};
+
+		if modifier.is_empty() || (self.others == 1 && modifier == key::SHIFT && code == 9) {
+			return None;
+		}
+
+		Some(format!("\x1B[27;{};{}~", modifiers(key), code).into_bytes())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::platform::key::Lock;
+
+	fn key(value: &str, modifier: key::Modifier, base: char) -> Key {
+		let mut key = Key::new(String::from(value).into(), modifier, Lock::empty());
+		key.set_base(Some(base));
+
+		key
+	}
+
+	#[test]
+	fn base() {
+		let mut keyboard = Keyboard::default();
+
+		keyboard.push(DISAMBIGUATE);
+		assert_eq!(keyboard.encode(&key("@", key::CTRL, ' ')), Some(b"\x1B[32;5u".to_vec()));
+
+		keyboard.push(ALL | ALTERNATES);
+		assert_eq!(keyboard.encode(&key("!", key::SHIFT, '1')), Some(b"\x1B[49:33;2u".to_vec()));
+	}
+}
@@ -21,8 +21,11 @@ pub use self::iter::Iter;
 pub mod cell;
 pub use self::cell::Cell;
 
+pub mod link;
+pub use self::link::{Link, Links};
+
 mod row;
-pub use self::row::Row;
+pub use self::row::{Row, Mark, Size};
 
 mod free;
 pub use self::free::Free;
@@ -45,8 +48,27 @@ pub use self::tabs::Tabs;
 mod input;
 pub use self::input::Input;
 
-mod sixel;
+mod sequence;
+pub use self::sequence::Sequence;
+
+mod terminfo;
+
+mod charset;
+
+pub mod keyboard;
+pub use self::keyboard::Keyboard;
+
+pub mod sixel;
 pub use self::sixel::Sixel;
 
+mod inline;
+pub use self::inline::Inline;
+
+pub mod graphics;
+pub use self::graphics::Graphics;
+
+pub mod regis;
+pub use self::regis::Regis;
+
 mod terminal;
 pub use self::terminal::Terminal;
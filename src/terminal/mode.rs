@@ -39,11 +39,75 @@ bitflags! {
 		                     MOUSE_MOTION.bits |
 		                     MOUSE_X10.bits    |
 		                     MOUSE_MANY.bits;
+
+		const ALTERNATE        = 1 << 17;
+		const MARGINS          = 1 << 18;
+		const SYNC             = 1 << 19;
+		const ALTERNATE_SCROLL = 1 << 20;
+
+		const MOUSE_UTF8     = 1 << 21;
+		const MOUSE_URXVT    = 1 << 22;
+		const MOUSE_PIXELS   = 1 << 23;
+		const MOUSE_ENCODING = MOUSE_SGR.bits   |
+		                       MOUSE_UTF8.bits  |
+		                       MOUSE_URXVT.bits |
+		                       MOUSE_PIXELS.bits;
+
+		const SIXEL_DISPLAY = 1 << 24;
+		const SIXEL_PRIVATE = 1 << 25;
+		const SIXEL_RIGHT   = 1 << 26;
+		const NRCS          = 1 << 27;
+		const RECTANGLE     = 1 << 28;
+	}
+}
+
+/// The ECMA modes that only toggle a flag.
+pub const ECMA: &[(u32, Mode)] = &[
+	(2,  KEYBOARD_LOCK),
+	(4,  INSERT),
+	(12, ECHO),
+	(20, CRLF),
+];
+
+/// The private modes that only toggle a flag, the ones with side effects are
+/// handled by the terminal.
+pub const PRIVATE: &[(u32, Mode)] = &[
+	(1,    APPLICATION_CURSOR),
+	(7,    WRAP),
+	(42,   NRCS),
+	(66,   APPLICATION_KEYPAD),
+	(80,   SIXEL_DISPLAY),
+	(1004, FOCUS),
+	(1007, ALTERNATE_SCROLL),
+	(1070, SIXEL_PRIVATE),
+	(2004, BRACKETED_PASTE),
+	(2026, SYNC),
+	(8452, SIXEL_RIGHT),
+];
+
+/// The state of a mode as reported through DECRPM.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum State {
+	Unrecognized     = 0,
+	Set              = 1,
+	Reset            = 2,
+	PermanentlySet   = 3,
+	PermanentlyReset = 4,
+}
+
+impl From<bool> for State {
+	fn from(value: bool) -> Self {
+		if value {
+			State::Set
+		}
+		else {
+			State::Reset
+		}
 	}
 }
 
 impl Default for Mode {
 	fn default() -> Self {
-		WRAP | UTF8
+		WRAP | UTF8 | SIXEL_PRIVATE
 	}
 }
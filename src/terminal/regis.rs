@@ -0,0 +1,831 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::str;
+use std::f64::consts::PI;
+
+use picto::color::{Rgb, Hsl, RgbHue};
+use crate::sys::cairo::{self, Context, Operator};
+use crate::terminal::sixel;
+
+/// The default ReGIS screen addressing.
+const ADDRESSING: ((f64, f64), (f64, f64)) = ((0.0, 0.0), (799.0, 479.0));
+
+/// The offset of each pixel vector direction.
+const DIRECTIONS: [(f64, f64); 8] = [
+	( 1.0,  0.0), ( 1.0, -1.0), (0.0, -1.0), (-1.0, -1.0),
+	(-1.0,  0.0), (-1.0,  1.0), (0.0,  1.0), ( 1.0,  1.0),
+];
+
+/// ReGIS state and the layer it draws into.
+#[derive(Debug)]
+pub struct Regis {
+	canvas:     Option<cairo::Image>,
+	addressing: ((f64, f64), (f64, f64)),
+	position:   (f64, f64),
+	colors:     sixel::Registers,
+	write:      Write,
+	text:       (f64, f64),
+}
+
+/// The write controls.
+#[derive(Copy, Clone, Debug)]
+struct Write {
+	color:      (f64, f64, f64),
+	mode:       Mode,
+	multiplier: f64,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Mode {
+	Overlay,
+	Replace,
+	Erase,
+	Complement,
+}
+
+/// The result of parsing a ReGIS header.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Header<'a> {
+	Done(&'a [u8], u32),
+	Incomplete,
+	Error,
+}
+
+/// A coordinate within a position argument.
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum Component {
+	Same,
+	Absolute(f64),
+	Relative(f64),
+}
+
+/// An argument of a command or option.
+#[derive(PartialEq, Clone, Debug)]
+enum Element<'a> {
+	Point(Component, Component),
+	Options(&'a str),
+	Text(String),
+	Vector(u8),
+	Number(f64),
+}
+
+/// Parse the header of a ReGIS device control string.
+pub fn header(i: &[u8]) -> Header {
+	for (index, &byte) in i.iter().enumerate() {
+		match byte {
+			b'0' ..= b'9' | b';' =>
+				continue,
+
+			b'p' => {
+				let mode = str::from_utf8(&i[.. index]).ok()
+					.and_then(|v| v.split(';').next())
+					.and_then(|v| v.parse().ok())
+					.unwrap_or(0);
+
+				return Header::Done(&i[index + 1 ..], mode);
+			}
+
+			_ =>
+				return Header::Error,
+		}
+	}
+
+	Header::Incomplete
+}
+
+/// Cursor over a ReGIS string.
+struct Input<'a> {
+	string: &'a str,
+	index:  usize,
+}
+
+impl<'a> Input<'a> {
+	fn new(string: &'a str) -> Self {
+		Input {
+			string: string,
+			index:  0,
+		}
+	}
+
+	fn peek(&self) -> Option<u8> {
+		self.string.as_bytes().get(self.index).cloned()
+	}
+
+	fn next(&mut self) -> Option<u8> {
+		let byte = self.peek();
+		self.index += 1;
+		byte
+	}
+
+	fn skip(&mut self) {
+		while let Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') | Some(b',') = self.peek() {
+			self.index += 1;
+		}
+	}
+
+	/// Read until the given byte, which is consumed.
+	fn until(&mut self, end: u8) -> &'a str {
+		let start = self.index;
+
+		while let Some(byte) = self.peek() {
+			if byte == end {
+				break;
+			}
+
+			self.index += 1;
+		}
+
+		let value = &self.string[start .. self.index];
+		self.index += 1;
+		value
+	}
+
+	/// Get the next argument, numbers are parsed as pixel vectors unless
+	/// `numbers` is set.
+	fn element(&mut self, numbers: bool) -> Option<Element<'a>> {
+		self.skip();
+
+		match self.peek()? {
+			b'[' => {
+				self.index += 1;
+
+				let     content = self.until(b']');
+				let mut parts   = content.splitn(2, ',').map(component);
+
+				Some(Element::Point(
+					parts.next().unwrap_or(Component::Same),
+					parts.next().unwrap_or(Component::Same)))
+			}
+
+			b'(' => {
+				self.index += 1;
+
+				let     start = self.index;
+				let mut depth = 1;
+
+				while let Some(byte) = self.next() {
+					match byte {
+						b'(' => depth += 1,
+						b')' => depth -= 1,
+						_    => (),
+					}
+
+					if depth == 0 {
+						return Some(Element::Options(&self.string[start .. self.index - 1]));
+					}
+				}
+
+				Some(Element::Options(&self.string[start ..]))
+			}
+
+			quote @ b'\'' | quote @ b'"' => {
+				self.index += 1;
+
+				// A doubled quote stands for the quote itself.
+				let mut text = String::from(self.until(quote));
+				while self.peek() == Some(quote) {
+					self.index += 1;
+					text.push(quote as char);
+					text.push_str(self.until(quote));
+				}
+
+				Some(Element::Text(text))
+			}
+
+			b'0' ..= b'9' | b'+' | b'-' | b'.' if numbers => {
+				let start = self.index;
+				self.index += 1;
+
+				while let Some(b'0' ..= b'9') | Some(b'.') = self.peek() {
+					self.index += 1;
+				}
+
+				self.string[start .. self.index].parse().ok().map(Element::Number)
+			}
+
+			digit @ b'0' ..= b'7' => {
+				self.index += 1;
+				Some(Element::Vector(digit - b'0'))
+			}
+
+			_ =>
+				None
+		}
+	}
+}
+
+/// Parse a coordinate.
+fn component(value: &str) -> Component {
+	let value = value.trim();
+
+	if value.is_empty() {
+		Component::Same
+	}
+	else if value.starts_with('+') || value.starts_with('-') {
+		value.parse().map(Component::Relative).unwrap_or(Component::Same)
+	}
+	else {
+		value.parse().map(Component::Absolute).unwrap_or(Component::Same)
+	}
+}
+
+/// Parse options into letters and their arguments.
+fn options(content: &str) -> Vec<(u8, Vec<Element>)> {
+	let mut input  = Input::new(content);
+	let mut result = Vec::new();
+
+	while let Some(byte) = input.next() {
+		if byte.is_ascii_alphabetic() {
+			let mut arguments = Vec::new();
+
+			while let Some(element) = input.element(true) {
+				arguments.push(element);
+			}
+
+			result.push((byte.to_ascii_uppercase(), arguments));
+		}
+	}
+
+	result
+}
+
+/// Get the first number in the arguments.
+fn number(arguments: &[Element]) -> Option<f64> {
+	arguments.iter().filter_map(|a|
+		if let Element::Number(n) = *a { Some(n) } else { None }).next()
+}
+
+/// Parse a color specifier, either a color letter or HLS values.
+fn color(spec: &str) -> Option<(f64, f64, f64)> {
+	let mut hls = (None, None, None);
+
+	for (letter, arguments) in options(spec) {
+		match (letter, number(&arguments)) {
+			(b'H', Some(n)) => hls.0 = Some(n),
+			(b'L', Some(n)) => hls.1 = Some(n),
+			(b'S', Some(n)) => hls.2 = Some(n),
+
+			(b'D', None) => return Some((0.0, 0.0, 0.0)),
+			(b'B', None) => return Some((0.0, 0.0, 1.0)),
+			(b'R', None) => return Some((1.0, 0.0, 0.0)),
+			(b'G', None) => return Some((0.0, 1.0, 0.0)),
+			(b'M', None) => return Some((1.0, 0.0, 1.0)),
+			(b'C', None) => return Some((0.0, 1.0, 1.0)),
+			(b'Y', None) => return Some((1.0, 1.0, 0.0)),
+			(b'W', None) => return Some((1.0, 1.0, 1.0)),
+
+			_ => ()
+		}
+	}
+
+	if hls == (None, None, None) {
+		return None;
+	}
+
+	// The DEC hue starts from blue.
+	let hue = (hls.0.unwrap_or(0.0) + 240.0) % 360.0;
+	let rgb = Rgb::from(Hsl::new(RgbHue::from_radians(hue as f32 * ::std::f32::consts::PI / 180.0),
+		hls.2.unwrap_or(0.0) as f32 / 100.0, hls.1.unwrap_or(0.0) as f32 / 100.0));
+
+	Some((rgb.red as f64, rgb.green as f64, rgb.blue as f64))
+}
+
+impl Regis {
+	/// Create an empty ReGIS state.
+	pub fn new() -> Self {
+		Regis {
+			canvas:     None,
+			addressing: ADDRESSING,
+			position:   (0.0, 0.0),
+			colors:     sixel::palette(),
+			write:      Write {
+				color:      (1.0, 1.0, 1.0),
+				mode:       Mode::Overlay,
+				multiplier: 1.0,
+			},
+			text: (1.0, 1.0),
+		}
+	}
+
+	/// Get the layer, if anything was drawn.
+	pub fn canvas(&self) -> Option<&cairo::Image> {
+		self.canvas.as_ref()
+	}
+
+	/// Reset the state and erase the layer.
+	pub fn reset(&mut self) {
+		*self = Regis::new();
+	}
+
+	/// Erase the layer.
+	pub fn clear(&mut self) {
+		self.canvas = None;
+	}
+
+	/// Handle ReGIS commands, drawing on a layer of the given size.
+	pub fn handle(&mut self, string: &str, (width, height): (u32, u32)) {
+		if self.canvas.as_ref().map(|c| (c.width(), c.height())) != Some((width, height)) {
+			self.canvas = Some(cairo::Image::new(width, height));
+		}
+
+		// The canvas is never replaced while handling the commands, so the
+		// surface and the context are dropped before it.
+		let     surface = unsafe { self.canvas.as_mut().unwrap().surface() };
+		let mut context = Context::new(&surface);
+		let mut input   = Input::new(string);
+
+		context.line_width(1.0);
+
+		while let Some(byte) = input.next() {
+			match byte.to_ascii_uppercase() {
+				b'P' => self.position(&mut input),
+				b'V' => self.vector(&mut input, &mut context),
+				b'C' => self.curve(&mut input, &mut context),
+				b'T' => self.text(&mut input, &mut context),
+				b'S' => self.screen(&mut input, &mut context),
+
+				b'W' => {
+					while let Some(element) = input.element(false) {
+						if let Element::Options(content) = element {
+							self.apply(content);
+						}
+					}
+				}
+
+				// Skip the arguments of unsupported commands.
+				b'A' ..= b'Z' => {
+					while input.element(false).is_some() { }
+				}
+
+				_ => ()
+			}
+		}
+	}
+
+	/// Convert ReGIS coordinates to user space coordinates within `path`.
+	fn path<F: FnOnce(&mut Context)>(&self, o: &mut Context, f: F) {
+		let ((x1, y1), (x2, y2)) = self.addressing;
+		let (width, height)      = self.canvas.as_ref().map(|c| (c.width(), c.height())).unwrap_or((0, 0));
+		let (dx, dy)             = (x2 - x1, y2 - y1);
+
+		o.save();
+		o.scale(width as f64 / (dx + dx.signum()), height as f64 / (dy + dy.signum()));
+		o.translate(-x1, -y1);
+		f(o);
+		o.restore();
+	}
+
+	/// Set the source and operator for the write controls.
+	fn paint(&self, o: &mut Context) {
+		let (r, g, b) = self.write.color;
+
+		match self.write.mode {
+			Mode::Overlay | Mode::Replace => {
+				o.operator(Operator::Over);
+				o.rgb(&Rgb::new(r, g, b));
+			}
+
+			Mode::Erase => {
+				o.operator(Operator::Clear);
+			}
+
+			Mode::Complement => {
+				o.operator(Operator::Difference);
+				o.rgb(&Rgb::new(1.0, 1.0, 1.0));
+			}
+		}
+	}
+
+	/// Apply write control options.
+	fn apply(&mut self, content: &str) {
+		for (letter, arguments) in options(content) {
+			match letter {
+				b'I' => match arguments.first() {
+					Some(&Element::Number(n)) =>
+						self.write.color = self.register(n as u32),
+
+					Some(&Element::Options(spec)) =>
+						if let Some(color) = color(spec) {
+							self.write.color = color;
+						},
+
+					_ => ()
+				},
+
+				b'V' => self.write.mode = Mode::Overlay,
+				b'R' => self.write.mode = Mode::Replace,
+				b'E' => self.write.mode = Mode::Erase,
+				b'C' => self.write.mode = Mode::Complement,
+
+				b'M' => if let Some(n) = number(&arguments) {
+					self.write.multiplier = n.max(1.0);
+				},
+
+				_ => ()
+			}
+		}
+	}
+
+	/// Get the color of a register.
+	fn register(&self, id: u32) -> (f64, f64, f64) {
+		let (r, g, b, _) = self.colors.get(&id).cloned().unwrap_or((0, 0, 0, 255));
+		(r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0)
+	}
+
+	/// Resolve a position argument against the current position.
+	fn resolve(&self, x: Component, y: Component) -> (f64, f64) {
+		fn resolve(current: f64, value: Component) -> f64 {
+			match value {
+				Component::Same        => current,
+				Component::Absolute(v) => v,
+				Component::Relative(v) => current + v,
+			}
+		}
+
+		(resolve(self.position.0, x), resolve(self.position.1, y))
+	}
+
+	/// Resolve a pixel vector against the current position.
+	fn step(&self, direction: u8) -> (f64, f64) {
+		let (dx, dy) = DIRECTIONS[direction as usize];
+
+		(self.position.0 + dx * self.write.multiplier,
+		 self.position.1 + dy * self.write.multiplier)
+	}
+
+	/// Handle a position command.
+	fn position(&mut self, input: &mut Input) {
+		let mut stack = Vec::new();
+
+		while let Some(element) = input.element(false) {
+			match element {
+				Element::Point(x, y) =>
+					self.position = self.resolve(x, y),
+
+				Element::Vector(direction) =>
+					self.position = self.step(direction),
+
+				Element::Options(content) => {
+					for (letter, _) in options(content) {
+						match letter {
+							b'B' => stack.push((true, self.position)),
+							b'S' => stack.push((false, self.position)),
+
+							b'E' => if let Some((true, start)) = stack.pop() {
+								self.position = start;
+							},
+
+							_ => ()
+						}
+					}
+				}
+
+				_ => ()
+			}
+		}
+	}
+
+	/// Handle a vector command.
+	fn vector(&mut self, input: &mut Input, o: &mut Context) {
+		let     saved = self.write;
+		let mut stack = Vec::new();
+
+		while let Some(element) = input.element(false) {
+			let (start, end) = match element {
+				Element::Point(Component::Same, Component::Same) =>
+					(self.position, self.position),
+
+				Element::Point(x, y) =>
+					(self.position, self.resolve(x, y)),
+
+				Element::Vector(direction) =>
+					(self.position, self.step(direction)),
+
+				Element::Options(content) => {
+					for (letter, arguments) in options(content) {
+						match letter {
+							b'B' => stack.push((true, self.position)),
+							b'S' => stack.push((false, self.position)),
+
+							// Bounded sequences go back to the start.
+							b'E' => if let Some((true, start)) = stack.pop() {
+								let end = self.position;
+								self.line(o, end, start);
+								self.position = start;
+							},
+
+							b'W' => if let Some(&Element::Options(content)) = arguments.first() {
+								self.apply(content);
+							},
+
+							_ => ()
+						}
+					}
+
+					continue;
+				}
+
+				_ =>
+					continue
+			};
+
+			self.line(o, start, end);
+			self.position = end;
+		}
+
+		self.write = saved;
+	}
+
+	/// Draw a line, or a dot if the ends are the same.
+	fn line(&self, o: &mut Context, start: (f64, f64), end: (f64, f64)) {
+		self.paint(o);
+
+		if start == end {
+			self.path(o, |o| o.rectangle(start.0, start.1, 1.0, 1.0));
+			o.fill();
+		}
+		else {
+			self.path(o, |o| {
+				o.move_to(start.0 + 0.5, start.1 + 0.5);
+				o.line_to(end.0 + 0.5, end.1 + 0.5);
+			});
+			o.stroke();
+		}
+	}
+
+	/// Handle a curve command.
+	fn curve(&mut self, input: &mut Input, o: &mut Context) {
+		let     saved  = self.write;
+		let mut center = false;
+		let mut arc    = None;
+		let mut points = None;
+
+		while let Some(element) = input.element(false) {
+			match element {
+				Element::Point(x, y) => {
+					let point = self.resolve(x, y);
+
+					if let Some((_, ref mut points)) = points {
+						Vec::push(points, point);
+						self.position = point;
+					}
+					else {
+						self.circle(o, point, center, arc);
+					}
+				}
+
+				Element::Options(content) => {
+					for (letter, arguments) in options(content) {
+						match letter {
+							b'C' => center = true,
+							b'A' => arc = Some(number(&arguments).unwrap_or(0.0)),
+							b'B' => points = Some((true, vec![self.position])),
+							b'S' => points = Some((false, vec![self.position])),
+
+							b'E' => if let Some((closed, points)) = points.take() {
+								self.spline(o, &points, closed);
+							},
+
+							b'W' => if let Some(&Element::Options(content)) = arguments.first() {
+								self.apply(content);
+							},
+
+							_ => ()
+						}
+					}
+				}
+
+				_ => ()
+			}
+		}
+
+		self.write = saved;
+	}
+
+	/// Draw a circle or an arc, either around the current position or with the
+	/// current position on the circumference.
+	fn circle(&mut self, o: &mut Context, point: (f64, f64), center: bool, arc: Option<f64>) {
+		let (middle, start) = if center {
+			(point, self.position)
+		}
+		else {
+			(self.position, point)
+		};
+
+		let radius = ((start.0 - middle.0).powi(2) + (start.1 - middle.1).powi(2)).sqrt();
+		let angle  = (start.1 - middle.1).atan2(start.0 - middle.0);
+
+		self.paint(o);
+		self.path(o, |o| {
+			let degrees = arc.unwrap_or(0.0);
+
+			if degrees == 0.0 || degrees.abs() >= 360.0 {
+				o.move_to(middle.0 + radius, middle.1);
+				o.arc(middle.0, middle.1, radius, 0.0, 2.0 * PI);
+			}
+			// Positive degrees go counterclockwise, and the Y axis points down.
+			else if degrees > 0.0 {
+				o.move_to(start.0, start.1);
+				o.arc_negative(middle.0, middle.1, radius, angle, angle - degrees.to_radians());
+			}
+			else {
+				o.move_to(start.0, start.1);
+				o.arc(middle.0, middle.1, radius, angle, angle - degrees.to_radians());
+			}
+		});
+		o.stroke();
+
+		// Arcs around a given center move the position to their end.
+		if let (true, Some(degrees)) = (center, arc) {
+			let end = angle - degrees.to_radians();
+			self.position = (middle.0 + radius * end.cos(), middle.1 + radius * end.sin());
+		}
+	}
+
+	/// Draw a curve through the given points.
+	fn spline(&mut self, o: &mut Context, points: &[(f64, f64)], closed: bool) {
+		if points.len() < 2 {
+			return;
+		}
+
+		let length = points.len() as isize;
+		let point  = |i: isize| if closed {
+			points[((i % length + length) % length) as usize]
+		}
+		else {
+			points[i.max(0).min(length - 1) as usize]
+		};
+
+		self.paint(o);
+		self.path(o, |o| {
+			o.move_to(points[0].0, points[0].1);
+
+			// Catmull-Rom segments between each pair of points.
+			for i in 0 .. if closed { length } else { length - 1 } {
+				let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+
+				o.curve_to(
+					p1.0 + (p2.0 - p0.0) / 6.0, p1.1 + (p2.1 - p0.1) / 6.0,
+					p2.0 - (p3.0 - p1.0) / 6.0, p2.1 - (p3.1 - p1.1) / 6.0,
+					p2.0, p2.1);
+			}
+		});
+		o.stroke();
+
+		self.position = if closed { points[0] } else { points[points.len() - 1] };
+	}
+
+	/// Handle a text command.
+	fn text(&mut self, input: &mut Input, o: &mut Context) {
+		let saved = self.write;
+
+		while let Some(element) = input.element(false) {
+			match element {
+				Element::Text(string) => {
+					// Standard characters are 9x20 at size 1.
+					let size = 20.0 * self.text.0 * self.text.1;
+
+					self.paint(o);
+
+					let mut end = self.position;
+					self.path(o, |o| {
+						o.move_to(self.position.0, self.position.1 + size * 0.8);
+						o.text(&string, size);
+						end = (o.position().0, self.position.1);
+					});
+
+					self.position = end;
+				}
+
+				Element::Options(content) => {
+					for (letter, arguments) in options(content) {
+						match letter {
+							b'S' => if let Some(n) = number(&arguments) {
+								self.text.0 = if n == 0.0 { 0.5 } else { n };
+							},
+
+							b'H' => if let Some(n) = number(&arguments) {
+								self.text.1 = n.max(1.0);
+							},
+
+							b'W' => if let Some(&Element::Options(content)) = arguments.first() {
+								self.apply(content);
+							},
+
+							_ => ()
+						}
+					}
+				}
+
+				_ => ()
+			}
+		}
+
+		self.write = saved;
+	}
+
+	/// Handle a screen command.
+	fn screen(&mut self, input: &mut Input, o: &mut Context) {
+		while let Some(element) = input.element(false) {
+			if let Element::Options(content) = element {
+				for (letter, arguments) in options(content) {
+					match letter {
+						b'E' => {
+							o.save();
+							o.operator(Operator::Clear);
+							o.paint();
+							o.restore();
+						}
+
+						b'A' => {
+							let mut corners = arguments.iter().filter_map(|a|
+								if let Element::Point(x, y) = *a { Some((x, y)) } else { None });
+
+							if let (Some(first), Some(second)) = (corners.next(), corners.next()) {
+								let absolute = |c: Component, default: f64|
+									if let Component::Absolute(v) = c { v } else { default };
+
+								let ((x1, y1), (x2, y2)) = self.addressing;
+								let ((x1, y1), (x2, y2)) = (
+									(absolute(first.0, x1), absolute(first.1, y1)),
+									(absolute(second.0, x2), absolute(second.1, y2)));
+
+								// A zero extent can't be scaled to the screen.
+								if x1 != x2 && y1 != y2 {
+									self.addressing = ((x1, y1), (x2, y2));
+								}
+							}
+						}
+
+						// Color map entries are a register followed by its color.
+						b'M' => {
+							let mut register = None;
+
+							for argument in &arguments {
+								match *argument {
+									Element::Number(n) =>
+										register = Some(n as u32),
+
+									Element::Options(spec) => {
+										if let (Some(id), Some((r, g, b))) = (register, color(spec)) {
+											self.colors.insert(id, ((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, 255));
+										}
+									}
+
+									_ => ()
+								}
+							}
+						}
+
+						_ => ()
+					}
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn header_mode() {
+		assert_eq!(header(b"pP[10,10]"), Header::Done(&b"P[10,10]"[..], 0));
+		assert_eq!(header(b"1pS(E)"), Header::Done(&b"S(E)"[..], 1));
+		assert_eq!(header(b"2;1p"), Header::Done(&b""[..], 2));
+		assert_eq!(header(b";1p"), Header::Done(&b""[..], 0));
+	}
+
+	#[test]
+	fn header_overflow() {
+		assert_eq!(header(b"99999999999p"), Header::Done(&b""[..], 0));
+	}
+
+	#[test]
+	fn header_incomplete() {
+		assert_eq!(header(b""), Header::Incomplete);
+		assert_eq!(header(b"1;"), Header::Incomplete);
+	}
+
+	#[test]
+	fn header_error() {
+		assert_eq!(header(b"1q"), Header::Error);
+		assert_eq!(header(b"P[10,10]"), Header::Error);
+	}
+}
@@ -25,6 +25,46 @@ use crate::terminal::Cell;
 pub struct Row {
 	pub(super) inner:   VecDeque<Cell>,
 	pub(super) wrapped: bool,
+	pub(super) marks:   Vec<Mark>,
+	pub(super) size:    Size,
+}
+
+/// The size of a line, set through DECDWL and DECDHL.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Size {
+	/// Normal line.
+	Single,
+
+	/// Double width line.
+	Wide,
+
+	/// Top half of a double height line.
+	Top,
+
+	/// Bottom half of a double height line.
+	Bottom,
+}
+
+impl Default for Size {
+	fn default() -> Self {
+		Size::Single
+	}
+}
+
+/// Semantic marks set by the shell through OSC 133.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Mark {
+	/// The prompt starts.
+	Prompt,
+
+	/// The command input starts.
+	Command,
+
+	/// The command output starts.
+	Output,
+
+	/// The command finished, with its exit status if known.
+	End(Option<i32>),
 }
 
 impl Row {
@@ -32,6 +72,24 @@ impl Row {
 	pub fn is_wrapped(&self) -> bool {
 		self.wrapped
 	}
+
+	/// Get the line size of the `Row`.
+	pub fn size(&self) -> Size {
+		self.size
+	}
+
+	/// Get the semantic marks on the `Row`.
+	pub fn marks(&self) -> &[Mark] {
+		&self.marks
+	}
+
+	/// Check if the `Row` has the given mark, ignoring any exit status.
+	pub fn is_marked(&self, mark: Mark) -> bool {
+		self.marks.iter().any(|m| match (*m, mark) {
+			(Mark::End(_), Mark::End(_)) => true,
+			(a, b)                       => a == b,
+		})
+	}
 }
 
 impl Deref for Row {
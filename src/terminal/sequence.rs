@@ -0,0 +1,411 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use control::SGR;
+use crate::style;
+
+/// A generic control sequence, used for the sequences `control` can't parse.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Sequence {
+	pub prefix:   Option<u8>,
+	pub args:     Vec<Vec<Option<u32>>>,
+	pub modifier: Option<u8>,
+	pub id:       u8,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Parsed<'a> {
+	Done(&'a [u8], Sequence),
+	Incomplete,
+	Error,
+}
+
+/// A graphic rendition, including the ones `control` can't parse.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Rendition {
+	Standard(SGR::T),
+	Underline(style::Attributes),
+	Decoration(SGR::Color),
+	Overline(bool),
+}
+
+impl Sequence {
+	/// Get the first value of the given parameter.
+	pub fn arg(&self, index: usize) -> Option<u32> {
+		self.args.get(index).and_then(|a| a.get(0).cloned()).and_then(|a| a)
+	}
+
+	/// Get the sub-parameters of the given parameter.
+	pub fn sub(&self, index: usize) -> &[Option<u32>] {
+		self.args.get(index).map(AsRef::as_ref).unwrap_or(&[])
+	}
+
+	/// Check if any parameter has sub-parameters.
+	pub fn has_sub(&self) -> bool {
+		self.args.iter().any(|a| a.len() > 1)
+	}
+}
+
+/// Parse a control sequence introducer with any prefix, parameters with
+/// sub-parameters and intermediate.
+pub fn parse(i: &[u8]) -> Parsed {
+	let mut rest = match i {
+		[0x1B, b'[', rest @ ..] => rest,
+		[0x9B, rest @ ..]       => rest,
+		[0x1B]                  => return Parsed::Incomplete,
+		_                       => return Parsed::Error,
+	};
+
+	let mut sequence = Sequence {
+		prefix:   None,
+		args:     Vec::new(),
+		modifier: None,
+		id:       0,
+	};
+
+	if let Some(&byte) = rest.first() {
+		if (b'<' ..= b'?').contains(&byte) {
+			sequence.prefix = Some(byte);
+			rest = &rest[1..];
+		}
+	}
+
+	let mut current = vec![None];
+	let mut any     = false;
+
+	loop {
+		let byte = match rest.first() {
+			Some(&byte) => byte,
+			None        => return Parsed::Incomplete,
+		};
+
+		match byte {
+			b'0' ..= b'9' => {
+				let value = current.last_mut().unwrap();
+				*value    = Some(value.unwrap_or(0).saturating_mul(10).saturating_add((byte - b'0') as u32));
+				any       = true;
+			}
+
+			b':' => {
+				current.push(None);
+				any = true;
+			}
+
+			b';' => {
+				sequence.args.push(current);
+				current = vec![None];
+				any     = true;
+			}
+
+			0x20 ..= 0x2F => {
+				sequence.modifier = Some(byte);
+			}
+
+			0x40 ..= 0x7E => {
+				if any {
+					sequence.args.push(current);
+				}
+
+				sequence.id = byte;
+				return Parsed::Done(&rest[1..], sequence);
+			}
+
+			_ =>
+				return Parsed::Error
+		}
+
+		rest = &rest[1..];
+	}
+}
+
+/// Parse the graphic renditions of a `SGR` sequence, including sub-parameters
+/// and the extended underline attributes.
+pub fn rendition(sequence: &Sequence) -> Option<Vec<Rendition>> {
+	if sequence.args.is_empty() {
+		return Some(vec![Rendition::Standard(SGR::Reset)]);
+	}
+
+	let mut result = Vec::new();
+	let mut args   = &sequence.args[..];
+
+	while let Some((current, rest)) = args.split_first() {
+		args = rest;
+
+		let id = current[0].unwrap_or(0);
+		result.push(match (id, current.len() > 1) {
+			(4, true) => Rendition::Underline(match current[1].unwrap_or(0) {
+				0 => style::NONE,
+				1 => style::UNDERLINE,
+				2 => style::DOUBLE,
+				3 => style::CURLY,
+				4 => style::DOTTED,
+				5 => style::DASHED,
+				_ => return None,
+			}),
+
+			(38, _) =>
+				Rendition::Standard(SGR::Foreground(color(current, &mut args)?)),
+
+			(48, _) =>
+				Rendition::Standard(SGR::Background(color(current, &mut args)?)),
+
+			(58, _) =>
+				Rendition::Decoration(color(current, &mut args)?),
+
+			(_, true) =>
+				return None,
+
+			(0, _) =>
+				Rendition::Standard(SGR::Reset),
+
+			(1, _) =>
+				Rendition::Standard(SGR::Font(SGR::Weight::Bold)),
+
+			(2, _) =>
+				Rendition::Standard(SGR::Font(SGR::Weight::Faint)),
+
+			(3, _) =>
+				Rendition::Standard(SGR::Italic(true)),
+
+			(4, _) =>
+				Rendition::Standard(SGR::Underline(true)),
+
+			(5, _) | (6, _) =>
+				Rendition::Standard(SGR::Blink(true)),
+
+			(7, _) =>
+				Rendition::Standard(SGR::Reverse(true)),
+
+			(8, _) =>
+				Rendition::Standard(SGR::Invisible(true)),
+
+			(9, _) =>
+				Rendition::Standard(SGR::Struck(true)),
+
+			(21, _) =>
+				Rendition::Underline(style::DOUBLE),
+
+			(22, _) =>
+				Rendition::Standard(SGR::Font(SGR::Weight::Normal)),
+
+			(23, _) =>
+				Rendition::Standard(SGR::Italic(false)),
+
+			(24, _) =>
+				Rendition::Standard(SGR::Underline(false)),
+
+			(25, _) =>
+				Rendition::Standard(SGR::Blink(false)),
+
+			(27, _) =>
+				Rendition::Standard(SGR::Reverse(false)),
+
+			(28, _) =>
+				Rendition::Standard(SGR::Invisible(false)),
+
+			(29, _) =>
+				Rendition::Standard(SGR::Struck(false)),
+
+			(c, _) if c >= 30 && c <= 37 =>
+				Rendition::Standard(SGR::Foreground(SGR::Color::Index(c as u8 - 30))),
+
+			(39, _) =>
+				Rendition::Standard(SGR::Foreground(SGR::Color::Default)),
+
+			(c, _) if c >= 40 && c <= 47 =>
+				Rendition::Standard(SGR::Background(SGR::Color::Index(c as u8 - 40))),
+
+			(49, _) =>
+				Rendition::Standard(SGR::Background(SGR::Color::Default)),
+
+			(53, _) =>
+				Rendition::Overline(true),
+
+			(55, _) =>
+				Rendition::Overline(false),
+
+			(59, _) =>
+				Rendition::Decoration(SGR::Color::Default),
+
+			(c, _) if c >= 90 && c <= 97 =>
+				Rendition::Standard(SGR::Foreground(SGR::Color::Index(c as u8 - 90 + 8))),
+
+			(c, _) if c >= 100 && c <= 107 =>
+				Rendition::Standard(SGR::Background(SGR::Color::Index(c as u8 - 100 + 8))),
+
+			_ =>
+				return None
+		});
+	}
+
+	Some(result)
+}
+
+/// Parse an extended color, either from the sub-parameters or from the
+/// following parameters.
+fn color(current: &[Option<u32>], args: &mut &[Vec<Option<u32>>]) -> Option<SGR::Color> {
+	if current.len() > 1 {
+		let value = |i: usize| current.get(i).cloned().and_then(|v| v).unwrap_or(0) as u8;
+
+		// The color space identifier is optional for direct colors.
+		return match current[1]? {
+			2 if current.len() > 5 =>
+				Some(SGR::Color::Rgb(value(3), value(4), value(5))),
+
+			2 =>
+				Some(SGR::Color::Rgb(value(2), value(3), value(4))),
+
+			5 =>
+				Some(SGR::Color::Index(value(2))),
+
+			_ =>
+				None
+		};
+	}
+
+	let mut next = || {
+		let rest: &[Vec<Option<u32>>] = *args;
+
+		if let Some((value, rest)) = rest.split_first() {
+			*args = rest;
+			value[0].unwrap_or(0)
+		}
+		else {
+			0
+		}
+	};
+
+	Some(match next() {
+		0 =>
+			SGR::Color::Default,
+
+		1 =>
+			SGR::Color::Transparent,
+
+		2 => {
+			let (r, g, b) = (next() as u8, next() as u8, next() as u8);
+			SGR::Color::Rgb(r, g, b)
+		}
+
+		3 => {
+			let (c, m, y) = (next() as u8, next() as u8, next() as u8);
+			SGR::Color::Cmy(c, m, y)
+		}
+
+		4 => {
+			let (c, m, y, k) = (next() as u8, next() as u8, next() as u8, next() as u8);
+			SGR::Color::Cmyk(c, m, y, k)
+		}
+
+		5 =>
+			SGR::Color::Index(next() as u8),
+
+		_ =>
+			return None
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sequence(prefix: Option<u8>, args: Vec<Vec<Option<u32>>>, modifier: Option<u8>, id: u8) -> Sequence {
+		Sequence { prefix, args, modifier, id }
+	}
+
+	#[test]
+	fn parameters() {
+		assert_eq!(parse(b"\x1B[4;2Hrest"),
+			Parsed::Done(&b"rest"[..], sequence(None, vec![vec![Some(4)], vec![Some(2)]], None, b'H')));
+
+		assert_eq!(parse(b"\x1B[;5H"),
+			Parsed::Done(&b""[..], sequence(None, vec![vec![None], vec![Some(5)]], None, b'H')));
+
+		assert_eq!(parse(b"\x1B[m"),
+			Parsed::Done(&b""[..], sequence(None, vec![], None, b'm')));
+
+		assert_eq!(parse(b"\x9B3A"),
+			Parsed::Done(&b""[..], sequence(None, vec![vec![Some(3)]], None, b'A')));
+	}
+
+	#[test]
+	fn sub_parameters() {
+		let parsed = parse(b"\x1B[4:3;58:2::255:0:0m");
+
+		assert_eq!(parsed, Parsed::Done(&b""[..], sequence(None, vec![
+			vec![Some(4), Some(3)],
+			vec![Some(58), Some(2), None, Some(255), Some(0), Some(0)]], None, b'm')));
+
+		if let Parsed::Done(_, sequence) = parsed {
+			assert!(sequence.has_sub());
+			assert_eq!(sequence.arg(0), Some(4));
+			assert_eq!(sequence.sub(0), &[Some(4), Some(3)]);
+			assert_eq!(sequence.sub(2), &[]);
+		}
+	}
+
+	#[test]
+	fn prefix_and_modifier() {
+		assert_eq!(parse(b"\x1B[?69h"),
+			Parsed::Done(&b""[..], sequence(Some(b'?'), vec![vec![Some(69)]], None, b'h')));
+
+		assert_eq!(parse(b"\x1B[2'}"),
+			Parsed::Done(&b""[..], sequence(None, vec![vec![Some(2)]], Some(b'\''), b'}')));
+	}
+
+	#[test]
+	fn overflow() {
+		assert_eq!(parse(b"\x1B[99999999999A"),
+			Parsed::Done(&b""[..], sequence(None, vec![vec![Some(u32::MAX)]], None, b'A')));
+	}
+
+	#[test]
+	fn incomplete() {
+		assert_eq!(parse(b"\x1B"), Parsed::Incomplete);
+		assert_eq!(parse(b"\x1B["), Parsed::Incomplete);
+		assert_eq!(parse(b"\x1B[1;2"), Parsed::Incomplete);
+	}
+
+	#[test]
+	fn error() {
+		assert_eq!(parse(b"a"), Parsed::Error);
+		assert_eq!(parse(b"\x1B]0;title"), Parsed::Error);
+		assert_eq!(parse(b"\x1B[1\x07"), Parsed::Error);
+	}
+
+	#[test]
+	fn kitty() {
+		assert_eq!(parse(b"\x1B[>1u"),
+			Parsed::Done(&b""[..], sequence(Some(b'>'), vec![vec![Some(1)]], None, b'u')));
+
+		assert_eq!(parse(b"\x1B[=5;2u"),
+			Parsed::Done(&b""[..], sequence(Some(b'='), vec![vec![Some(5)], vec![Some(2)]], None, b'u')));
+
+		assert_eq!(parse(b"\x1B[<u"),
+			Parsed::Done(&b""[..], sequence(Some(b'<'), vec![], None, b'u')));
+	}
+
+	#[test]
+	fn underline() {
+		let parsed = match parse(b"\x1B[4:3m") {
+			Parsed::Done(_, sequence) => sequence,
+			_                         => panic!("not parsed"),
+		};
+
+		assert_eq!(rendition(&parsed), Some(vec![Rendition::Underline(style::CURLY)]));
+	}
+}
@@ -19,11 +19,36 @@ use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 use fnv::FnvHasher;
 use std::f32;
+use std::cmp;
 
 use picto::color::{Rgba, Hsl, RgbHue};
 use crate::control::DEC::SIXEL;
 use crate::sys::cairo;
 
+/// The number of color registers available.
+pub const REGISTERS: u32 = 1024;
+
+/// Maximum height of the raster area filled with the background.
+const HEIGHT: u32 = 4096;
+
+/// Color registers.
+pub type Registers = HashMap<u32, (u8, u8, u8, u8), BuildHasherDefault<FnvHasher>>;
+
+/// The default VT340 color registers.
+pub fn palette() -> Registers {
+	const DEFAULT: [(u8, u8, u8); 16] = [
+		( 0,  0,  0), (20, 20, 80), (80, 13, 13), (20, 80, 20),
+		(80, 20, 80), (20, 80, 80), (80, 80, 20), (53, 53, 53),
+		(26, 26, 26), (33, 33, 60), (60, 26, 26), (33, 60, 33),
+		(60, 33, 60), (33, 60, 60), (60, 60, 33), (80, 80, 80),
+	];
+
+	DEFAULT.iter().enumerate().map(|(i, &(r, g, b))| {
+		let percent = |v: u8| (v as u32 * 255 / 100) as u8;
+		(i as u32, (percent(r), percent(g), percent(b), 255))
+	}).collect()
+}
+
 #[derive(Debug)]
 pub struct Sixel {
 	raster: SIXEL::Header,
@@ -34,13 +59,14 @@ pub struct Sixel {
 	limit:    (u32, u32),
 	position: (u32, u32),
 
-	colors:     HashMap<u32, (u8, u8, u8, u8), BuildHasherDefault<FnvHasher>>,
+	colors:     Registers,
+	registers:  u32,
 	color:      (u8, u8, u8, u8),
 	background: (u8, u8, u8, u8),
 }
 
 impl Sixel {
-	pub fn new(origin: (u32, u32), header: SIXEL::Header, background: &Rgba<f64>, cell: (u32, u32), limit: (u32, u32)) -> Self {
+	pub fn new(origin: (u32, u32), header: SIXEL::Header, background: &Rgba<f64>, cell: (u32, u32), limit: (u32, u32), colors: Registers, registers: u32) -> Self {
 		Sixel {
 			raster: header,
 			origin: origin,
@@ -50,7 +76,8 @@ impl Sixel {
 			limit:    limit,
 			position: (0, 0),
 
-			colors:     Default::default(),
+			colors:     colors,
+			registers:  registers,
 			color:      (0, 0, 0, 255),
 			background: (
 				(background.red   * 255.0) as u8,
@@ -68,16 +95,62 @@ impl Sixel {
 		self.grid.len()
 	}
 
-	pub fn into_inner(self) -> Vec<Vec<cairo::Image>> {
-		self.grid
+	/// Get the drawn cells and the color registers.
+	pub fn into_inner(self) -> (Vec<Vec<cairo::Image>>, Registers) {
+		(self.grid, self.colors)
 	}
 
 	pub fn aspect(&mut self, aspect: (u32, u32)) {
 		self.raster.aspect = aspect;
 	}
 
+	/// The number of pixels each sixel bit covers vertically.
+	fn ratio(&self) -> u32 {
+		let (numerator, denominator) = self.raster.aspect;
+		cmp::max(1, (numerator + denominator / 2) / cmp::max(denominator, 1))
+	}
+
+	/// Get the cell at the given position in the local grid, extending it as
+	/// needed.
+	fn cell(&mut self, x: usize, y: usize) -> &mut cairo::Image {
+		// If the grid doesn't have enough rows, extend it.
+		while y >= self.grid.len() {
+			self.grid.push(Vec::new());
+		}
+
+		// If the grid doesn't have enough columns, extend it.
+		while x >= self.grid[y].len() {
+			self.grid[y].push(cairo::Image::new(self.cell.0, self.cell.1));
+		}
+
+		&mut self.grid[y][x]
+	}
+
+	/// Fill the raster area with the background, if enabled.
+	pub fn size(&mut self, (width, height): (u32, u32)) {
+		if !self.raster.background {
+			return;
+		}
+
+		let background = self.background;
+
+		for y in 0 .. cmp::min(height, HEIGHT) {
+			for x in 0 .. width {
+				let column = x / self.cell.0;
+
+				// Stop at the terminal limit.
+				if column + self.limit.0 >= self.limit.1 {
+					break;
+				}
+
+				let (xo, yo) = (x % self.cell.0, y % self.cell.1);
+				self.cell(column as usize, (y / self.cell.1) as usize).set(xo, yo, &background);
+			}
+		}
+	}
+
 	pub fn enable(&mut self, id: u32) {
-		self.color = self.colors.get(&id).unwrap_or(&self.background).clone();
+		self.color = self.colors.get(&(id % self.registers)).unwrap_or(&self.background).clone();
 	}
 
 	pub fn define(&mut self, id: u32, color: SIXEL::Color) {
@@ -93,7 +166,7 @@ impl Sixel {
 				(r, g, b, a),
 		};
 
-		self.colors.insert(id, color);
+		self.colors.insert(id % self.registers, color);
 	}
 
 	pub fn start(&mut self) {
@@ -102,7 +175,7 @@ impl Sixel {
 
 	pub fn next(&mut self) {
 		self.position.0  = 0;
-		self.position.1 += 6 * self.raster.aspect.0;
+		self.position.1 += 6 * self.ratio();
 	}
 
 	pub fn draw(&mut self, times: u32, value: SIXEL::Map) {
@@ -113,6 +186,9 @@ impl Sixel {
 			return;
 		}
 
+		let ratio = self.ratio();
+		let color = self.color;
+
 		for _ in 0 .. times {
 			// The X within the local grid.
 			let x = (self.position.0 / self.cell.0) as usize;
@@ -125,9 +201,9 @@ impl Sixel {
 			// The X within the image buffer.
 			let xo = self.position.0 % self.cell.0;
 
-			for (i, y) in (self.position.1 .. self.position.1 + (6 * self.raster.aspect.0)).enumerate() {
+			for (i, y) in (self.position.1 .. self.position.1 + (6 * ratio)).enumerate() {
 				// The bit index within the sixel map.
-				let bit = (i as u32 / self.raster.aspect.0) as u8;
+				let bit = (i as u32 / ratio) as u8;
 
 				// The Y within the image buffer.
 				let yo = y as u32 % self.cell.1;
@@ -135,23 +211,14 @@ impl Sixel {
 				// The Y within the grid.
 				let y = (y / self.cell.1) as usize;
 
-				// If the grid doesn't have enough rows, extend it.
-				while y >= self.grid.len() {
-					self.grid.push(Vec::new());
-				}
-
-				// If the grid doesn't have enough columns, extend it.
-				while x >= self.grid[y].len() {
-					self.grid[y].push(cairo::Image::new(self.cell.0, self.cell.1));
-				}
-
 				// If the bit is enabled, set it.
 				if value.get(bit) {
-					self.grid[y][x].set(xo, yo, &self.color);
+					self.cell(x, y).set(xo, yo, &color);
 				}
 				// If disabled bits should set the background color, do so.
 				else if self.raster.background {
-					self.grid[y][x].set(xo, yo, &self.background);
+					let background = self.background;
+					self.cell(x, y).set(xo, yo, &background);
 				}
 			}
 
@@ -161,8 +228,9 @@ impl Sixel {
 
 	pub fn handle(&mut self, item: &SIXEL::T) {
 		match *item {
-			SIXEL::Raster { aspect, .. } => {
+			SIXEL::Raster { aspect, size } => {
 				self.aspect(aspect);
+				self.size(size);
 			}
 
 			SIXEL::Enable(id) => {
@@ -16,12 +16,16 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ops::Index;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::fs;
 use std::mem;
 use std::vec;
 use std::str;
 use std::cmp;
+use std::time::{Duration, Instant};
 
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
@@ -30,22 +34,40 @@ use picto::color::Rgba;
 use control::{self, Control, C0, C1, DEC, CSI, SGR};
 use crate::util;
 use crate::error;
+use crate::sys::cairo;
 use crate::config::{self, Config};
 use crate::config::style::Shape;
 use crate::style::{self, Style};
 use crate::platform::key::{self, Key};
 use crate::platform::mouse::{self, Mouse};
-use crate::terminal::{Iter, Touched, Cell, Tabs, Grid, Sixel, cell};
+use crate::platform::Clipboard;
+use crate::terminal::{Iter, Touched, Cell, Tabs, Grid, Sixel, Inline, Graphics, Regis, Link, Links, Mark, Size, Keyboard, cell};
+use crate::terminal::graphics;
+use crate::terminal::keyboard;
+use crate::terminal::sixel;
+use crate::terminal::charset;
+use crate::terminal::regis;
 use crate::terminal::mode::{self, Mode};
 use crate::terminal::cursor::{self, Cursor};
 use crate::terminal::touched;
 use crate::terminal::input::{self, Input};
+use crate::terminal::sequence::{self, Sequence, Rendition};
+use crate::terminal::terminfo;
 use crate::interface::Action;
 
+/// How long to wait for the answer to a clipboard query.
+const QUERY: Duration = Duration::from_secs(5);
+
+/// The maximum depth of the title stack.
+const TITLES: usize = 10;
+
 #[derive(Debug)]
 pub struct Terminal {
-	config: Arc<Config>,
-	font:   (u32, u32),
+	config:   Arc<Config>,
+	defaults: Arc<Config>,
+	font:     (u32, u32),
+	screen:   (u32, u32),
+	process:  Option<i32>,
 
 	region:  Region,
 	cache:   Option<Vec<u8>>,
@@ -53,13 +75,35 @@ pub struct Terminal {
 	mode:    Mode,
 	click:   Option<mouse::Click>,
 	command: Option<Command>,
-
-	scroll: Option<u32>,
-	grid:   Grid,
-	tabs:   Tabs,
+	query:   Option<(Clipboard, Instant)>,
+
+	scroll:    Option<u32>,
+	grid:      Grid,
+	alternate: Grid,
+	tabs:      Tabs,
+	graphics:  Graphics,
+	regis:     Regis,
+	palette:   sixel::Registers,
+	registers: u32,
+	shift:     Option<u8>,
+
+	links:   Links,
+	link:    Option<Rc<Link>>,
+	hover:   Option<Rc<Link>>,
+	hovered: Vec<u32>,
+
+	directory: Option<PathBuf>,
+	download:  Option<(Option<String>, Vec<u8>)>,
+	keyboard:  Keyboard,
+
+	title:   String,
+	icon:    String,
+	titles:  Vec<(Option<String>, Option<String>)>,
+	caption: String,
+	status:  (String, String),
 
 	cursor: Cursor,
-	saved:  Option<Cursor>,
+	saved:  [Option<Cursor>; 2],
 }
 
 unsafe impl Send for Terminal { }
@@ -68,6 +112,8 @@ unsafe impl Send for Terminal { }
 enum Command {
 	Device,
 	Sixel(Sixel),
+	Regis,
+	Application,
 	Internal,
 }
 
@@ -76,10 +122,33 @@ macro_rules! term {
 		$term.cursor.charsets[$term.cursor.charset as usize]
 	);
 
+	($term:ident; saved) => (
+		$term.saved[if $term.mode.contains(mode::ALTERNATE) { 1 } else { 0 }]
+	);
+
+	($term:ident; columns) => (
+		if $term.cursor.margin == (0, $term.region.width - 1) {
+			None
+		}
+		else {
+			Some($term.cursor.margin)
+		}
+	);
+
+	($term:ident; unhover) => ({
+		if $term.hover.take().is_some() {
+			for y in $term.hovered.drain(..) {
+				$term.touched.line(y);
+			}
+		}
+	});
+
 	($term:ident; scroll! up $n:tt) => (
-		if $term.cursor.scroll == (0, $term.region.height - 1) {
+		if $term.cursor.scroll == (0, $term.region.height - 1) && term!($term; columns).is_none() {
+			term!($term; unhover);
 			$term.touched.all();
-			$term.grid.up($n, None);
+			$term.grid.up($n, None, None);
+			$term.graphics.up($n, None, $term.grid.back().len());
 		}
 		else {
 			term!($term; scroll up $n)
@@ -92,7 +161,12 @@ macro_rules! term {
 
 	($term:ident; scroll up $n:tt from $y:expr) => ({
 		if $y < $term.cursor.scroll.1 {
-			$term.grid.up($n as u32, Some(($y, $term.cursor.scroll.1)));
+			term!($term; unhover);
+			$term.grid.up($n as u32, Some(($y, $term.cursor.scroll.1)), term!($term; columns));
+
+			if $term.graphics.up($n as u32, Some(($y, $term.cursor.scroll.1)), 0) {
+				$term.touched.all();
+			}
 
 			for y in $y ..= $term.cursor.scroll.1 {
 				$term.touched.line(y);
@@ -106,7 +180,12 @@ macro_rules! term {
 
 	($term:ident; scroll down $n:tt from $y:expr) => ({
 		if $y < $term.cursor.scroll.1 {
-			$term.grid.down($n as u32, Some(($y, $term.cursor.scroll.1)));
+			term!($term; unhover);
+			$term.grid.down($n as u32, Some(($y, $term.cursor.scroll.1)), term!($term; columns));
+
+			if $term.graphics.down($n as u32, ($y, $term.cursor.scroll.1)) {
+				$term.touched.all();
+			}
 
 			for y in $y ..= $term.cursor.scroll.1 {
 				$term.touched.line(y);
@@ -129,13 +208,36 @@ macro_rules! term {
 	($term:ident; cursor $($travel:tt)*) => ({
 		$term.touched.push($term.cursor.position());
 		let r = $term.cursor.travel(cursor::$($travel)*);
+		term!($term; line);
 		$term.touched.push($term.cursor.position());
 		r
 	});
 
+	($term:ident; line) => (
+		$term.cursor.line($term.grid[$term.cursor.y()].size() != Size::Single)
+	);
+
 	($term:ident; tab $n:expr) => ({
 		let (x, _) = term!($term; cursor);
-		term!($term; cursor Position(Some($term.tabs.next($n, x)), None));
+		term!($term; cursor Absolute(Some($term.tabs.next($n, x)), None));
+	});
+
+	($term:ident; clear) => ({
+		for y in 0 .. $term.region.height {
+			for x in 0 .. $term.region.width {
+				$term.grid[(x, y)].make_empty($term.cursor.style().clone());
+			}
+
+			$term.grid.wrapped(y, false);
+			$term.grid.unmark(y);
+			$term.grid.size(y, Size::Single);
+		}
+
+		term!($term; line);
+
+		$term.graphics.clear();
+		$term.regis.clear();
+		$term.touched.all();
 	});
 
 	($term:ident; clean references ($x:expr, $y:expr)) => ({
@@ -143,18 +245,33 @@ macro_rules! term {
 			$term.grid.clean_references($x, $y);
 		}
 	});
+
+	($term:ident; clean head ($x:expr, $y:expr)) => ({
+		let (x, y) = ($x, $y);
+
+		if $term.grid[(x, y)].is_reference() {
+			for head in x - $term.grid[(x, y)].offset() .. x {
+				$term.grid[(head, y)].make_empty($term.cursor.style().clone());
+				$term.touched.mark(head, y);
+			}
+		}
+	});
 }
 
 impl Terminal {
-	/// Create a new terminal.
-	pub fn new(config: Arc<Config>, font: (u32, u32), (width, height): (u32, u32)) -> error::Result<Self> {
+	/// Create a new terminal, optionally for the given process.
+	pub fn new(config: Arc<Config>, font: (u32, u32), (width, height): (u32, u32), process: Option<i32>) -> error::Result<Self> {
 		let region = Region::from(0, 0, width, height);
 		let grid   = Grid::new(width, height, config.environment().scroll());
+		let alt    = Grid::new(width, height, 0);
 		let tabs   = Tabs::new(width, height);
 
 		Ok(Terminal {
-			config: config.clone(),
-			font:   font,
+			config:   config.clone(),
+			defaults: config.clone(),
+			font:     font,
+			screen:   (width * font.0, height * font.1),
+			process:  process,
 
 			region:  region,
 			cache:   Default::default(),
@@ -162,13 +279,35 @@ impl Terminal {
 			mode:    Mode::default(),
 			click:   None,
 			command: None,
-
-			scroll: None,
-			grid:   grid,
-			tabs:   tabs,
+			query:   None,
+
+			scroll:    None,
+			grid:      grid,
+			alternate: alt,
+			tabs:      tabs,
+			graphics:  Graphics::new(),
+			regis:     Regis::new(),
+			palette:   sixel::palette(),
+			registers: sixel::REGISTERS,
+			shift:     None,
+
+			links:   Links::default(),
+			link:    None,
+			hover:   None,
+			hovered: Vec::new(),
+
+			directory: None,
+			download:  None,
+			keyboard:  Keyboard::default(),
+
+			title:   String::from("cancer"),
+			icon:    String::from("cancer"),
+			titles:  Vec::new(),
+			caption: String::from("cancer"),
+			status:  Default::default(),
 
 			cursor: Cursor::new(config.clone(), width, height),
-			saved:  None,
+			saved:  [None, None],
 		})
 	}
 
@@ -197,12 +336,105 @@ impl Terminal {
 		&self.grid
 	}
 
+	/// Get the stored images.
+	pub fn graphics(&self) -> &Graphics {
+		&self.graphics
+	}
+
+	/// Get the images and the ReGIS layer visible in the given rows.
+	pub fn visible(&self, offset: i64, rows: u32) -> Vec<graphics::Visible> {
+		let mut visible = self.graphics.visible(offset, rows);
+
+		if let Some(canvas) = self.regis.canvas() {
+			visible.push(graphics::Visible {
+				image:  canvas,
+				x:      0,
+				y:      offset,
+				offset: (0, 0),
+				source: (0, 0, canvas.width(), canvas.height()),
+				size:   (canvas.width() as f64, canvas.height() as f64),
+				z:      0,
+				limit:  rows,
+			});
+		}
+
+		visible
+	}
+
+	/// Get the line size of the given row.
+	pub fn size(&self, y: u32) -> Size {
+		self.grid[y].size()
+	}
+
 	/// Get the cursor.
 	pub fn cursor(&self) -> cursor::Cell {
 		let (x, y) = term!(self; cursor);
 		cursor::Cell::new(&self.cursor, cell::Position::new(x, y, &self.grid[(x, y)]))
 	}
 
+	/// Get the hyperlink under the mouse, if any.
+	pub fn hover(&self) -> Option<&Rc<Link>> {
+		self.hover.as_ref()
+	}
+
+	/// Get the working directory of the program, as announced through OSC 7
+	/// or as seen by the system.
+	pub fn directory(&self) -> Option<PathBuf> {
+		if let Some(path) = self.directory.as_ref() {
+			return Some(path.clone());
+		}
+
+		self.process.and_then(|id| fs::read_link(format!("/proc/{}/cwd", id)).ok())
+	}
+
+	/// Get the name of the foreground process as seen by the system.
+	pub fn foreground(&self) -> Option<String> {
+		let stat  = fs::read_to_string(format!("/proc/{}/stat", self.process?)).ok()?;
+		let group = stat[stat.rfind(')')? + 2 ..].split(' ').nth(5)?;
+
+		fs::read_to_string(format!("/proc/{}/comm", group)).ok()
+			.map(|name| name.trim_end().into())
+	}
+
+	/// Check the directory and the foreground process again, returning the
+	/// new window title if it changed.
+	pub fn refresh(&mut self) -> Option<String> {
+		let template = self.config.environment().title();
+
+		if template.contains("{directory}") {
+			self.status.0 = self.directory().map(|p| p.display().to_string()).unwrap_or_default();
+		}
+
+		if template.contains("{process}") {
+			self.status.1 = self.foreground().unwrap_or_default();
+		}
+
+		self.caption()
+	}
+
+	/// Render the window title from the configured template, using the last
+	/// seen directory and foreground process, returning it if it changed.
+	fn caption(&mut self) -> Option<String> {
+		let caption = self.config.environment().title()
+			.replace("{title}", &self.title)
+			.replace("{icon}", &self.icon)
+			.replace("{directory}", &self.status.0)
+			.replace("{process}", &self.status.1);
+
+		if caption != self.caption {
+			self.caption = caption;
+			Some(self.caption.clone())
+		}
+		else {
+			None
+		}
+	}
+
+	/// Take the last file sent by the program, if any.
+	pub fn download(&mut self) -> Option<(Option<String>, Vec<u8>)> {
+		self.download.take()
+	}
+
 	/// Get the region of the terminal.
 	pub fn region(&self) -> Region {
 		self.region
@@ -213,27 +445,219 @@ impl Terminal {
 		Iter::new(self, iter)
 	}
 
+	/// Change the size in pixels of the screen the window is on, it's only
+	/// used for reports.
+	pub fn set_screen(&mut self, width: u32, height: u32) {
+		self.screen = (width, height);
+	}
+
 	/// Resize the terminal.
 	pub fn resize(&mut self, width: u32, height: u32) {
 		self.region.width  = width;
 		self.region.height = height;
 
 		self.tabs.resize(width, height);
+		term!(self; unhover);
+
+		fn travel(value: &mut Cursor, offset: i32) {
+			match offset {
+				n if n > 0 => {
+					value.travel(cursor::Down(n as u32));
+				}
+
+				n if n < 0 => {
+					value.travel(cursor::Up((-n) as u32));
+				}
 
-		match self.grid.resize(width, height) {
-			n if n > 0 => {
-				self.cursor.travel(cursor::Down(n as u32));
+				_ => ()
 			}
+		}
+
+		let current = self.grid.resize(width, height);
+		let other   = self.alternate.resize(width, height);
+
+		travel(&mut self.cursor, current);
+		self.cursor.resize(width, height);
 
-			n if n < 0 => {
-				self.cursor.travel(cursor::Up((-n) as u32));
+		// The cursor saved on the inactive primary screen has to follow the
+		// reflow of its grid.
+		if self.mode.contains(mode::ALTERNATE) {
+			if let Some(saved) = self.saved[0].as_mut() {
+				travel(saved, other);
+				saved.resize(width, height);
 			}
+		}
+		else {
+			self.saved[0] = None;
+		}
 
-			_ => ()
+		if let Some(saved) = self.saved[1].as_mut() {
+			saved.resize(width, height);
 		}
+	}
 
-		self.cursor.resize(width, height);
-		self.saved = None;
+	/// Switch between the primary and alternate screen.
+	fn screen(&mut self, alternate: bool) {
+		if alternate == self.mode.contains(mode::ALTERNATE) {
+			return;
+		}
+
+		term!(self; unhover);
+		mem::swap(&mut self.grid, &mut self.alternate);
+		self.graphics.swap();
+		self.mode.toggle(mode::ALTERNATE);
+		self.keyboard.screen(alternate);
+		self.touched.all();
+	}
+
+	/// Save the cursor state for the current screen (DECSC).
+	fn save(&mut self) {
+		term!(self; saved) = Some(self.cursor.clone());
+	}
+
+	/// Restore the cursor state for the current screen (DECRC), without a
+	/// saved state the cursor goes home with the default rendition.
+	fn restore(&mut self) {
+		let saved = term!(self; saved).clone().unwrap_or_else(||
+			Cursor::new(self.config.clone(), self.region.width, self.region.height));
+
+		self.touched.push(term!(self; cursor));
+		self.cursor.restore(&saved);
+		self.touched.push(term!(self; cursor));
+	}
+
+	/// Reset the modes and cursor state (DECSTR).
+	fn soft_reset(&mut self) {
+		self.mode.remove(mode::INSERT | mode::KEYBOARD_LOCK | mode::APPLICATION_CURSOR |
+			mode::APPLICATION_KEYPAD | mode::MARGINS);
+		self.mode.insert(mode::WRAP);
+
+		self.cursor.reset();
+		self.shift = None;
+		term!(self; saved) = None;
+
+		self.touched.push(term!(self; cursor));
+	}
+
+	/// Reset the terminal to its initial state (RIS).
+	fn hard_reset(&mut self) {
+		let (width, height) = (self.region.width, self.region.height);
+
+		self.screen(false);
+
+		self.config    = self.defaults.clone();
+		self.mode      = Mode::default();
+		self.grid      = Grid::new(width, height, self.config.environment().scroll());
+		self.alternate = Grid::new(width, height, 0);
+		self.tabs      = Tabs::new(width, height);
+		self.graphics  = Graphics::new();
+		self.regis     = Regis::new();
+		self.palette   = sixel::palette();
+		self.registers = sixel::REGISTERS;
+		self.shift     = None;
+		self.keyboard  = Keyboard::default();
+		self.links     = Links::default();
+		self.link      = None;
+		self.hover     = None;
+		self.hovered   = Vec::new();
+		self.title     = String::from("cancer");
+		self.icon      = String::from("cancer");
+		self.titles    = Vec::new();
+		self.cursor    = Cursor::new(self.config.clone(), width, height);
+		self.saved     = [None, None];
+
+		self.touched.all();
+	}
+
+	/// Get the rectangle described by the four parameters starting at `index`
+	/// as inclusive `(left, top, right, bottom)`, taking origin mode into
+	/// account.
+	fn rectangle(&self, sequence: &Sequence, index: usize) -> Option<(u32, u32, u32, u32)> {
+		let ((x, y), (width, height)) = if self.cursor.state.contains(cursor::ORIGIN) {
+			((self.cursor.margin.0, self.cursor.scroll.0), (self.cursor.margin.1, self.cursor.scroll.1))
+		}
+		else {
+			((0, 0), (self.region.width - 1, self.region.height - 1))
+		};
+
+		let get = |offset: usize, origin: u32, limit: u32, default: u32| {
+			let value = match sequence.arg(index + offset) {
+				Some(0) | None => default,
+				Some(n)        => n,
+			};
+
+			cmp::min(origin.saturating_add(value - 1), limit)
+		};
+
+		let top    = get(0, y, height, 1);
+		let left   = get(1, x, width, 1);
+		let bottom = get(2, y, height, height - y + 1);
+		let right  = get(3, x, width, width - x + 1);
+
+		if top > bottom || left > right {
+			None
+		}
+		else {
+			Some((left, top, right, bottom))
+		}
+	}
+
+	/// Erase the cells in the rectangle, optionally leaving the protected ones
+	/// alone.
+	fn erase(&mut self, (left, top, right, bottom): (u32, u32, u32, u32), selective: bool) {
+		for y in top ..= bottom {
+			// A wide character straddling the left edge is erased whole.
+			let     start = if self.grid[(left, y)].is_reference() {
+				left - self.grid[(left, y)].offset()
+			}
+			else {
+				left
+			};
+			let mut keep = false;
+
+			for x in start ..= right {
+				// References go away with the character they belong to.
+				if !self.grid[(x, y)].is_reference() {
+					keep = selective && self.grid[(x, y)].style().attributes().contains(style::PROTECTED);
+				}
+
+				if keep {
+					continue;
+				}
+
+				self.grid[(x, y)].make_empty(self.cursor.style().clone());
+				self.touched.mark(x, y);
+			}
+
+			if !keep {
+				term!(self; clean references (right + 1, y));
+			}
+		}
+	}
+
+	/// Change the attributes of the cells in the rectangle, or in the stream of
+	/// cells between its corners unless DECSACE asked for rectangles.
+	fn attributes<F: Fn(&mut style::Attributes)>(&mut self, (left, top, right, bottom): (u32, u32, u32, u32), f: F) {
+		for y in top ..= bottom {
+			let (start, end) = if self.mode.contains(mode::RECTANGLE) {
+				(left, right)
+			}
+			else {
+				(if y == top { left } else { 0 }, if y == bottom { right } else { self.region.width - 1 })
+			};
+
+			for x in start ..= end {
+				if self.grid[(x, y)].is_reference() {
+					continue;
+				}
+
+				let mut style = **self.grid[(x, y)].style();
+				f(&mut style.attributes);
+
+				self.grid[(x, y)].set_style(Rc::new(style));
+				self.touched.mark(x, y);
+			}
+		}
 	}
 
 	/// Enable or disable blinking and return the affected cells.
@@ -288,6 +712,22 @@ impl Terminal {
 		Ok(())
 	}
 
+	/// Answer a pending clipboard query, the answer is dropped if it's not for
+	/// the last query or it took too long to arrive.
+	pub fn answer<O: Write>(&mut self, name: Clipboard, value: &[u8], mut output: O) -> io::Result<()> {
+		match self.query.take() {
+			Some((query, when)) if query == name && when.elapsed() < QUERY =>
+				write!(output, "\x1B]52;{};{}\x1B\\", match name {
+					Clipboard::Primary   => 'p',
+					Clipboard::Secondary => 'q',
+					Clipboard::System    => 'c',
+				}, util::base64::encode(value)),
+
+			_ =>
+				Ok(())
+		}
+	}
+
 	/// Handle a key.
 	pub fn key<O: Write>(&mut self, key: Key, mut output: O) -> io::Result<()> {
 		use crate::platform::key::{Value, Button, Keypad};
@@ -330,6 +770,24 @@ impl Terminal {
 
 		debug!(target: "cancer::terminal::key", "key {:?}", key);
 
+		if let Some(sequence) = self.keyboard.encode(&key) {
+			return output.write_all(&sequence);
+		}
+
+		// Releases are only reported through the keyboard protocol.
+		if key.kind() == key::Kind::Release {
+			return Ok(());
+		}
+
+		// Modified function and cursor keys always use the xterm encoding.
+		if let Some((number, end, implied)) = keyboard::function(key.value()) {
+			let modifiers = keyboard::modifiers(&key) - 1;
+
+			if modifiers != 0 {
+				return output.write_all(format!("\x1B[{};{}{}", number, (modifiers | implied) + 1, end as char).as_bytes());
+			}
+		}
+
 		match *key.value() {
 			Value::Char(ref string) => {
 				if key.modifier().contains(key::ALT) {
@@ -378,45 +836,24 @@ impl Terminal {
 			},
 
 			Value::Button(Button::Delete) => write! {
-				CTRL # APPLICATION_KEYPAD => b"\x1B[3;5~",
-				CTRL                      => b"\x1B[M",
-
-				SHIFT # APPLICATION_KEYPAD => b"\x1B[3;2~",
-				SHIFT                      => b"\x1B[2K",
-
 				_ # APPLICATION_KEYPAD => b"\x1B[3~",
 				_                      => b"\x1B[P",
 			},
 
 			Value::Button(Button::Insert) |
 			Value::Keypad(Keypad::Insert) => write! {
-				CTRL # APPLICATION_KEYPAD => b"\x1B[2;5~",
-				CTRL                      => b"\x1B[L",
-
-				SHIFT # APPLICATION_KEYPAD => b"\x1B[2;2~",
-				SHIFT                      => b"\x1B[4l",
-
 				_ # APPLICATION_KEYPAD => b"\x1B[2~",
 				_                      => b"\x1B[M",
 			},
 
 			Value::Button(Button::Home) |
 			Value::Keypad(Keypad::Home) => write! {
-				SHIFT # APPLICATION_CURSOR => b"\x1B[1;2H",
-				SHIFT                      => b"\x1B[2J",
-
 				_ # APPLICATION_CURSOR => b"\x1B[H",
 				_                      => b"\x1B[7~",
 			},
 
 			Value::Button(Button::End) |
 			Value::Keypad(Keypad::End) => write! {
-				CTRL # APPLICATION_KEYPAD => b"\x1B[1;5F",
-				CTRL                      => b"\x1B[J",
-
-				SHIFT # APPLICATION_KEYPAD => b"\x1B[1;2F",
-				SHIFT                      => b"\x1B[K",
-
 				_ => b"\x1B[8~",
 			},
 
@@ -426,257 +863,150 @@ impl Terminal {
 
 			Value::Button(Button::PageUp) |
 			Value::Keypad(Keypad::PageUp) => write! {
-				CTRL  => b"\x1B[5;5~",
-				SHIFT => b"\x1B[5;2~",
-				_     => b"\x1B[5~",
+				_ => b"\x1B[5~",
 			},
 
 			Value::Button(Button::PageDown) |
 			Value::Keypad(Keypad::PageDown) => write! {
-				CTRL  => b"\x1B[6;5~",
-				SHIFT => b"\x1B[6;2~",
-				_     => b"\x1B[6~",
+				_ => b"\x1B[6~",
 			},
 
 			Value::Button(Button::Up) |
 			Value::Keypad(Keypad::Up) => write! {
-				CTRL  => b"\x1B[1;5A",
-				ALT   => b"\x1B[1;3A",
-				SHIFT => b"\x1B[1;2A",
-
 				_ # APPLICATION_CURSOR => b"\x1BOA",
 				_                      => b"\x1B[A",
 			},
 
 			Value::Button(Button::Down) |
 			Value::Keypad(Keypad::Down) => write! {
-				CTRL  => b"\x1B[1;5B",
-				ALT   => b"\x1B[1;3B",
-				SHIFT => b"\x1B[1;2B",
-
 				_ # APPLICATION_CURSOR => b"\x1BOB",
 				_                      => b"\x1B[B",
 			},
 
 			Value::Button(Button::Right) |
 			Value::Keypad(Keypad::Right) => write! {
-				CTRL  => b"\x1B[1;5C",
-				ALT   => b"\x1B[1;3C",
-				SHIFT => b"\x1B[1;2C",
-
 				_ # APPLICATION_CURSOR => b"\x1BOC",
 				_                      => b"\x1B[C",
 			},
 
 			Value::Button(Button::Left) |
 			Value::Keypad(Keypad::Left) => write! {
-				CTRL  => b"\x1B[1;5D",
-				ALT   => b"\x1B[1;3D",
-				SHIFT => b"\x1B[1;2D",
-
 				_ # APPLICATION_CURSOR => b"\x1BOD",
 				_                      => b"\x1B[D",
 			},
 
-			Value::Button(Button::F(1)) => write! {
-				CTRL  => b"\x1B[1;5P",
-				ALT   => b"\x1B[1;3P",
-				LOGO  => b"\x1B[1;6P",
-				SHIFT => b"\x1B[1;2P",
-				_     => b"\x1BOP",
-			},
-
-			Value::Button(Button::F(2)) => write! {
-				CTRL  => b"\x1B[1;5Q",
-				ALT   => b"\x1B[1;3Q",
-				LOGO  => b"\x1B[1;6Q",
-				SHIFT => b"\x1B[1;2Q",
-				_     => b"\x1BOQ",
-			},
-
-			Value::Button(Button::F(3)) => write! {
-				CTRL  => b"\x1B[1;5R",
-				ALT   => b"\x1B[1;3R",
-				LOGO  => b"\x1B[1;6R",
-				SHIFT => b"\x1B[1;2R",
-				_     => b"\x1BOR",
-			},
-
-			Value::Button(Button::F(4)) => write! {
-				CTRL  => b"\x1B[1;5S",
-				ALT   => b"\x1B[1;3S",
-				LOGO  => b"\x1B[1;6S",
-				SHIFT => b"\x1B[1;2S",
-				_     => b"\x1BOS",
-			},
-
-			Value::Button(Button::F(5)) => write! {
-				CTRL  => b"\x1B[15;5~",
-				ALT   => b"\x1B[15;3~",
-				LOGO  => b"\x1B[15;6~",
-				SHIFT => b"\x1B[15;2~",
-				_     => b"\x1B[15~",
-			},
-
-			Value::Button(Button::F(6)) => write! {
-				CTRL  => b"\x1B[17;5~",
-				ALT   => b"\x1B[17;3~",
-				LOGO  => b"\x1B[17;6~",
-				SHIFT => b"\x1B[17;2~",
-				_     => b"\x1B[17~",
-			},
-
-			Value::Button(Button::F(7)) => write! {
-				CTRL  => b"\x1B[18;5~",
-				ALT   => b"\x1B[18;3~",
-				LOGO  => b"\x1B[18;6~",
-				SHIFT => b"\x1B[18;2~",
-				_     => b"\x1B[18~",
-			},
-
-			Value::Button(Button::F(8)) => write! {
-				CTRL  => b"\x1B[19;5~",
-				ALT   => b"\x1B[19;3~",
-				LOGO  => b"\x1B[19;6~",
-				SHIFT => b"\x1B[19;2~",
-				_     => b"\x1B[19~",
-			},
-
-			Value::Button(Button::F(9)) => write! {
-				CTRL  => b"\x1B[20;5~",
-				ALT   => b"\x1B[20;3~",
-				LOGO  => b"\x1B[20;6~",
-				SHIFT => b"\x1B[20;2~",
-				_     => b"\x1B[20~",
-			},
-
-			Value::Button(Button::F(10)) => write! {
-				CTRL  => b"\x1B[21;5~",
-				ALT   => b"\x1B[21;3~",
-				LOGO  => b"\x1B[21;6~",
-				SHIFT => b"\x1B[21;2~",
-				_     => b"\x1B[21~",
-			},
-
-			Value::Button(Button::F(11)) => write! {
-				CTRL  => b"\x1B[23;5~",
-				ALT   => b"\x1B[23;3~",
-				LOGO  => b"\x1B[23;6~",
-				SHIFT => b"\x1B[23;2~",
-				_     => b"\x1B[23~",
-			},
-
-			Value::Button(Button::F(12)) => write! {
-				CTRL  => b"\x1B[24;5~",
-				ALT   => b"\x1B[24;3~",
-				LOGO  => b"\x1B[24;6~",
-				SHIFT => b"\x1B[24;2~",
-				_     => b"\x1B[24~",
-			},
-
-			Value::Button(Button::F(13)) => write! {
-				_ => b"\x1B[1;2P",
-			},
-
-			Value::Button(Button::F(14)) => write! {
-				_ => b"\x1B[1;2Q",
-			},
-
-			Value::Button(Button::F(15)) => write! {
-				_ => b"\x1B[1;2R",
-			},
-
-			Value::Button(Button::F(16)) => write! {
-				_ => b"\x1B[1;2S",
-			},
+			Value::Button(Button::F(_)) => match keyboard::function(key.value()) {
+				Some((_, end, 0)) if end != b'~' =>
+					output.write_all(&[0x1B, b'O', end]),
 
-			Value::Button(Button::F(17)) => write! {
-				_ => b"\x1B[15;2~",
-			},
+				Some((number, _, 0)) =>
+					output.write_all(format!("\x1B[{}~", number).as_bytes()),
 
-			Value::Button(Button::F(18)) => write! {
-				_ => b"\x1B[17;2~",
-			},
+				Some((number, end, implied)) =>
+					output.write_all(format!("\x1B[{};{}{}", number, implied + 1, end as char).as_bytes()),
 
-			Value::Button(Button::F(19)) => write! {
-				_ => b"\x1B[18;2~",
+				None =>
+					Ok(())
 			},
 
-			Value::Button(Button::F(20)) => write! {
-				_ => b"\x1B[19;2~",
-			},
+			_ =>
+				Ok(())
+		}
+	}
 
-			Value::Button(Button::F(21)) => write! {
-				_ => b"\x1B[20;2~",
-			},
+	/// Handle mouse inputs and return the affected cells.
+	pub fn mouse<O: Write>(&mut self, mouse: Mouse, output: O) -> io::Result<touched::Iter> {
+		debug!(target: "cancer::terminal::mouse", "mouse {:?}", mouse);
 
-			Value::Button(Button::F(22)) => write! {
-				_ => b"\x1B[21;2~",
-			},
+		if let Mouse::Motion(motion) = mouse {
+			self.hovering(motion.position.x, motion.position.y);
+		}
 
-			Value::Button(Button::F(23)) => write! {
-				_ => b"\x1B[23;2~",
-			},
+		self.report(mouse, output)?;
+		Ok(self.touched.iter(self.region))
+	}
 
-			Value::Button(Button::F(24)) => write! {
-				_ => b"\x1B[24;2~",
-			},
+	/// Update the hyperlink under the mouse, marking the rows of the old and
+	/// new one.
+	///
+	/// The rows of the hovered link are remembered, and the hover is dropped
+	/// whenever they move, so only finding a new link needs a scan.
+	fn hovering(&mut self, x: u32, y: u32) {
+		if x >= self.region.width || y >= self.region.height {
+			return;
+		}
 
-			Value::Button(Button::F(25)) => write! {
-				_ => b"\x1B[1;5P",
-			},
+		let x = if let Cell::Reference(offset) = self.grid[(x, y)] {
+			x - offset as u32
+		}
+		else {
+			x
+		};
 
-			Value::Button(Button::F(26)) => write! {
-				_ => b"\x1B[1;5Q",
-			},
+		let link = self.grid[(x, y)].link().cloned();
 
-			Value::Button(Button::F(27)) => write! {
-				_ => b"\x1B[1;5R",
-			},
+		match (link.as_ref(), self.hover.as_ref()) {
+			(Some(a), Some(b)) if Rc::ptr_eq(a, b) =>
+				return,
 
-			Value::Button(Button::F(28)) => write! {
-				_ => b"\x1B[1;5S",
-			},
+			(None, None) =>
+				return,
 
-			Value::Button(Button::F(29)) => write! {
-				_ => b"\x1B[15;5~",
-			},
+			_ => ()
+		}
 
-			Value::Button(Button::F(30)) => write! {
-				_ => b"\x1B[17;5~",
-			},
+		term!(self; unhover);
 
-			Value::Button(Button::F(31)) => write! {
-				_ => b"\x1B[18;5~",
-			},
+		if let Some(link) = link {
+			let width  = self.region.width;
+			let height = self.region.height;
+			let grid   = &self.grid;
 
-			Value::Button(Button::F(32)) => write! {
-				_ => b"\x1B[19;5~",
-			},
+			let contains = |y: u32|
+				(0 .. width).any(|x| grid[(x, y)].link().map_or(false, |l| Rc::ptr_eq(l, &link)));
 
-			Value::Button(Button::F(33)) => write! {
-				_ => b"\x1B[20;5~",
-			},
+			// Links without an ID are printed in one go, so they can only span the
+			// rows around the mouse, while shared ones can be anywhere.
+			let rows = if link.id().is_some() {
+				(0 .. height).filter(|&y| contains(y)).collect::<Vec<_>>()
+			}
+			else {
+				let top    = (0 .. y).rev().take_while(|&y| contains(y)).last().unwrap_or(y);
+				let bottom = (y + 1 .. height).take_while(|&y| contains(y)).last().unwrap_or(y);
 
-			Value::Button(Button::F(34)) => write! {
-				_ => b"\x1B[21;5~",
-			},
+				(top ..= bottom).collect()
+			};
 
-			Value::Button(Button::F(35)) => write! {
-				_ => b"\x1B[23;5~",
-			},
+			for &y in &rows {
+				self.touched.line(y);
+			}
 
-			_ =>
-				Ok(())
+			self.hover   = Some(link);
+			self.hovered = rows;
 		}
 	}
 
-	/// Handle mouse inputs.
-	pub fn mouse<O: Write>(&mut self, mouse: Mouse, mut output: O) -> io::Result<()> {
-		debug!(target: "cancer::terminal::mouse", "mouse {:?}", mouse);
-
+	/// Report mouse inputs to the program.
+	fn report<O: Write>(&mut self, mouse: Mouse, mut output: O) -> io::Result<()> {
 		// If none of the mouse reporting modes are set, bail out.
 		if !self.mode.intersects(mode::MOUSE) {
+			// The wheel moves the cursor on the alternate screen with alternate scroll.
+			if self.mode.contains(mode::ALTERNATE | mode::ALTERNATE_SCROLL) {
+				if let Mouse::Click(mouse::Click { press: true, button, .. }) = mouse {
+					let application = self.mode.contains(mode::APPLICATION_CURSOR);
+
+					match button {
+						mouse::Button::Up =>
+							output.write_all(if application { b"\x1BOA" } else { b"\x1B[A" })?,
+
+						mouse::Button::Down =>
+							output.write_all(if application { b"\x1BOB" } else { b"\x1B[B" })?,
+
+						_ => ()
+					}
+				}
+			}
+
 			return Ok(());
 		}
 
@@ -688,8 +1018,11 @@ impl Terminal {
 			Mouse::Motion(motion) => {
 				// If no button is being clicked, motions aren't reported.
 				if let Some(mut click) = self.click {
-					// Don't report the same position twice.
-					if click.position == motion.position {
+					// Don't report the same position twice, pixel positions are only
+					// relevant when reporting pixels.
+					if (click.position.x, click.position.y) == (motion.position.x, motion.position.y) &&
+					   (!self.mode.contains(mode::MOUSE_PIXELS) || click.position.pixels == motion.position.pixels)
+					{
 						return Ok(());
 					}
 
@@ -718,7 +1051,7 @@ impl Terminal {
 			self.click = Some(click);
 		}
 
-		let mut button = if !self.mode.contains(mode::MOUSE_SGR) && !click.press {
+		let mut button = if !self.mode.intersects(mode::MOUSE_SGR | mode::MOUSE_PIXELS) && !click.press {
 			3
 		}
 		else {
@@ -752,14 +1085,39 @@ impl Terminal {
 				x      = click.position.x + 1,
 				y      = click.position.y + 1)?;
 		}
-		else if click.position.x < 223 && click.position.y < 223 {
-			output.write_all(b"\x1B[M")?;
-			output.write_all(&[
-				32 + button,
-				32 + click.position.x as u8 + 1,
-				32 + click.position.y as u8 + 1])?;
+		else if self.mode.contains(mode::MOUSE_PIXELS) {
+			write!(output, "\x1B[<{button};{x};{y}{mode}",
+				mode   = if click.press { 'M' } else { 'm' },
+				button = button,
+				x      = click.position.pixels.0 + 1,
+				y      = click.position.pixels.1 + 1)?;
 		}
-
+		else if self.mode.contains(mode::MOUSE_URXVT) {
+			write!(output, "\x1B[{button};{x};{y}M",
+				button = 32 + button,
+				x      = click.position.x + 1,
+				y      = click.position.y + 1)?;
+		}
+		else if self.mode.contains(mode::MOUSE_UTF8) {
+			// Coordinates are encoded as UTF-8 characters, up to 2015.
+			if click.position.x < 2015 && click.position.y < 2015 {
+				let mut buffer = String::from("\x1B[M");
+
+				for &value in &[32 + button as u32, 32 + click.position.x + 1, 32 + click.position.y + 1] {
+					buffer.push(char::from_u32(value).unwrap_or(' '));
+				}
+
+				output.write_all(buffer.as_bytes())?;
+			}
+		}
+		else if click.position.x < 223 && click.position.y < 223 {
+			output.write_all(b"\x1B[M")?;
+			output.write_all(&[
+				32 + button,
+				32 + click.position.x as u8 + 1,
+				32 + click.position.y as u8 + 1])?;
+		}
+
 		Ok(())
 	}
 
@@ -794,11 +1152,27 @@ impl Terminal {
 
 								input = rest;
 
-								let origin = term!(self; cursor);
-								let sixel  = Sixel::new(origin, header,
-									self.cursor.style().background().unwrap_or(self.config.style().color().background()),
+								// Without sixel scrolling the image is drawn from the top left.
+								let origin = if self.mode.contains(mode::SIXEL_DISPLAY) {
+									(0, 0)
+								}
+								else {
+									term!(self; cursor)
+								};
+
+								// Private color registers start from the default palette.
+								let colors = if self.mode.contains(mode::SIXEL_PRIVATE) {
+									sixel::palette()
+								}
+								else {
+									self.palette.clone()
+								};
+
+								let sixel = Sixel::new(origin, header,
+									self.cursor.style().resolve(self.config.color()).background().unwrap_or(self.config.style().color().background()),
 									(self.font.0, self.font.1),
-									(origin.0, self.region.width));
+									(origin.0, self.region.width),
+									colors, self.registers);
 
 								self.command = Some(Command::Sixel(sixel));
 								continue;
@@ -814,7 +1188,45 @@ impl Terminal {
 							control::Result::Error(..) => ()
 						}
 
-						// TODO: insert ReGIS header check
+						match regis::header(input) {
+							regis::Header::Done(rest, mode) => {
+								debug!(target: "cancer::terminal::input::regis", "regis {}", mode);
+
+								input = rest;
+
+								if mode & 1 == 1 {
+									self.regis.reset();
+								}
+
+								self.command = Some(Command::Regis);
+								continue;
+							}
+
+							regis::Header::Incomplete => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Device);
+								break;
+							}
+
+							regis::Header::Error => ()
+						}
+
+						match C1::string(input) {
+							control::Result::Done(rest, item) => {
+								input = rest;
+								self.device(item, output.by_ref())?;
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Device);
+								break;
+							}
+
+							control::Result::Error(..) => ()
+						}
 					}
 
 					Command::Sixel(mut sixel) => {
@@ -825,29 +1237,35 @@ impl Terminal {
 
 								input = rest;
 
-								let rows = sixel.rows();
-								let edge = sixel.origin().0;
+								let origin            = sixel.origin();
+								let (rows, registers) = sixel.into_inner();
 
-								// Move each row into the grid.
-								for (i, row) in sixel.into_inner().into_iter().enumerate() {
-									let (x, y) = term!(self; cursor);
+								if !self.mode.contains(mode::SIXEL_PRIVATE) {
+									self.palette = registers;
+								}
 
-									for (offset, buffer) in row.into_iter().enumerate() {
-										self.grid[(x + offset as u32, y)].make_image(buffer, self.cursor.style().clone());
-										term!(self; cursor Right(1));
-									}
+								if self.mode.contains(mode::SIXEL_DISPLAY) {
+									// The image doesn't scroll and the cursor doesn't move.
+									let saved = self.cursor.clone();
 
-									// Clean leftover references.
-									let (x, y) = term!(self; cursor);
-									self.grid.clean_references(x - 1, y);
+									term!(self; cursor Absolute(Some(origin.0), Some(origin.1)));
+									self.image(rows, origin.0, false);
 
-									// If it's the last row, skip cursor movement.
-									if i < rows - 1 {
+									self.touched.push(term!(self; cursor));
+									self.cursor = saved;
+									self.touched.push(term!(self; cursor));
+								}
+								else {
+									self.image(rows, origin.0, true);
+
+									// The cursor goes to the line below the image, unless it
+									// should stay on its right.
+									if !self.mode.contains(mode::SIXEL_RIGHT) {
 										if term!(self; cursor Down(1)).is_some() {
 											term!(self; scroll! up 1);
 										}
 
-										term!(self; cursor Position(Some(edge), None));
+										term!(self; cursor Absolute(Some(origin.0), None));
 									}
 								}
 
@@ -886,11 +1304,52 @@ impl Terminal {
 						}
 					}
 
+					Command::Regis => {
+						match C1::string(input) {
+							control::Result::Done(rest, item) => {
+								debug!(target: "cancer::terminal::input::regis", "regis {:?}", item);
+
+								input = rest;
+								self.regis.handle(item, (self.region.width * self.font.0, self.region.height * self.font.1));
+								self.touched.all();
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Regis);
+								break;
+							}
+
+							control::Result::Error(..) =>
+								break,
+						}
+					}
+
+					Command::Application => {
+						match C1::string(input) {
+							control::Result::Done(rest, item) => {
+								input = rest;
+								self.application(item, output.by_ref())?;
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Application);
+								break;
+							}
+
+							control::Result::Error(..) =>
+								break,
+						}
+					}
+
 					Command::Internal => {
 						match C1::string(input) {
 							control::Result::Done(rest, item) => {
 								input = rest;
-								actions.extend(self.command(item));
+								actions.extend(self.command(item, output.by_ref())?);
 							}
 
 							control::Result::Incomplete(..) => {
@@ -909,6 +1368,50 @@ impl Terminal {
 				continue;
 			}
 
+			// Locking shifts the control parser doesn't know about.
+			if let [0x1B, id @ (b'n' | b'o' | b'~' | b'}' | b'|'), rest @ ..] = input {
+				debug!(target: "cancer::terminal::input::parsed", "locking shift: {}", *id as char);
+
+				input = rest;
+
+				match *id {
+					b'n' => self.cursor.charset = 2,
+					b'o' => self.cursor.charset = 3,
+					b'~' => self.cursor.right = 1,
+					b'}' => self.cursor.right = 2,
+					b'|' => self.cursor.right = 3,
+					_    => unreachable!()
+				}
+
+				continue;
+			}
+
+			// Try to parse the sequences the control parser doesn't know about.
+			match sequence::parse(input) {
+				sequence::Parsed::Done(rest, ref sequence) => {
+					debug!(target: "cancer::terminal::input::parsed", "sequence: {:?}", sequence);
+
+					if self.sequence(sequence, output.by_ref())? {
+						input = rest;
+						continue;
+					}
+
+					if control::parse(input).is_err() {
+						debug!(target: "cancer::terminal::unhandled", "unhandled sequence: {:?}", sequence);
+						input = rest;
+						continue;
+					}
+				}
+
+				sequence::Parsed::Incomplete => {
+					debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+					self.cache = Some(input.to_vec());
+					break;
+				}
+
+				sequence::Parsed::Error => ()
+			}
+
 			// Try to parse the input.
 			let item = match control::parse(input) {
 				// No control code.
@@ -977,286 +1480,729 @@ impl Terminal {
 			actions.extend(self.control(item, output.by_ref())?);
 		}
 
+		// The directory and the foreground process are only checked through
+		// `refresh`, so this doesn't touch the system.
+		if let Some(caption) = self.caption() {
+			actions.push(Action::Title(caption));
+		}
+
 		Ok((actions.into_iter(), self.touched.iter(self.region)))
 	}
 
-	fn control<O: Write>(&mut self, control: Control, mut output: O) -> error::Result<Vec<Action>> {
-		let mut actions = Vec::new();
+	/// Set, reset or query an ECMA or private mode, returns the state of the
+	/// mode after the change.
+	///
+	/// Setting and reporting go through the same table, so any mode that can
+	/// be changed is also reported.
+	fn switch(&mut self, private: bool, id: u32, value: Option<bool>, actions: &mut Vec<Action>) -> mode::State {
+		let table = if private { mode::PRIVATE } else { mode::ECMA };
 
-		match control {
-			// Attributes.
-			Control::C1(C1::ControlSequence(CSI::DeviceAttributes(0))) => {
-				output.write_all(b"\033[?64;6;21c")?;
+		// Modes that only toggle a flag.
+		if let Some(&(_, flag)) = table.iter().find(|&&(i, _)| i == id) {
+			match value {
+				Some(true)  => self.mode.insert(flag),
+				Some(false) => self.mode.remove(flag),
+				None        => (),
 			}
 
-			Control::C1(C1::ControlSequence(CSI::DeviceStatusReport(CSI::Report::CursorPosition))) => {
-				control::format_to(output.by_ref(),
-					&CSI::CursorPositionReport { x: self.cursor.x(), y: self.cursor.y() })?;
-			}
+			return self.mode.contains(flag).into();
+		}
 
-			Control::DEC(DEC::Unicode(value)) => {
-				if value {
-					self.mode.insert(mode::UTF8);
-				}
-				else {
-					self.mode.remove(mode::UTF8);
-				}
+		if !private {
+			if value.is_some() {
+				debug!(target: "cancer::terminal::unhandled", "unhandled ECMA mode: {} {:?}", id, value);
 			}
 
-			Control::DEC(DEC::ScrollRegion { top, bottom }) => {
-				let mut top    = top;
-				let mut bottom = bottom.unwrap_or(self.region.height);
+			return mode::State::Unrecognized;
+		}
 
-				top    = util::clamp(top, 0, self.region.height - 1);
-				bottom = util::clamp(bottom, 0, self.region.height - 1);
+		match id {
+			// There's no VT52 mode and key repeat is up to the window system.
+			2 | 8 =>
+				mode::State::PermanentlySet,
 
-				if top > bottom {
-					mem::swap(&mut top, &mut bottom);
+			// Scrolling is always jump scrolling.
+			4 =>
+				mode::State::PermanentlyReset,
+
+			3 => {
+				if let Some(value) = value {
+					actions.push(Action::Resize(if value { 132 } else { 80 }, 24));
+					return value.into();
 				}
 
-				self.cursor.scroll = (top, bottom);
-				term!(self; cursor Position(Some(0), Some(0)));
+				(self.region.width == 132).into()
 			}
 
-			Control::C1(C1::ControlSequence(CSI::Set(modes))) => {
-				debug!(target: "cancer::terminal::mode::set", "set ECMA modes: {:?}", modes);
-
-				for mode in modes {
-					match mode {
-						CSI::Mode::KeyboardLock =>
-							self.mode.insert(mode::KEYBOARD_LOCK),
-
-						CSI::Mode::InsertionReplacement =>
-							self.mode.insert(mode::INSERT),
+			5 => {
+				match value {
+					Some(true)  => self.mode.insert(mode::REVERSE),
+					Some(false) => self.mode.remove(mode::REVERSE),
+					None        => (),
+				}
 
-						CSI::Mode::SendReceive =>
-							self.mode.insert(mode::ECHO),
+				if value.is_some() {
+					self.touched.all();
+				}
 
-						CSI::Mode::LineFeed =>
-							self.mode.insert(mode::CRLF),
+				self.mode.contains(mode::REVERSE).into()
+			}
 
-						mode =>
-							debug!(target: "cancer::terminal::unhandled", "unhandled set: {:?}", mode)
+			6 => {
+				match value {
+					Some(true) => {
+						self.cursor.state.insert(cursor::ORIGIN);
+						term!(self; cursor Position(Some(0), Some(0)));
 					}
-				}
-			}
 
-			Control::DEC(DEC::Set(modes)) => {
-				debug!(target: "cancer::terminal::mode::set", "set DEC modes: {:?}", modes);
+					Some(false) => {
+						self.cursor.state.remove(cursor::ORIGIN);
+						self.touched.push(term!(self; cursor));
+					}
 
-				for mode in modes {
-					match mode {
-						DEC::Mode::ApplicationCursor =>
-							self.mode.insert(mode::APPLICATION_CURSOR),
+					None => ()
+				}
 
-						DEC::Mode::ReverseVideo => {
-							self.mode.insert(mode::REVERSE);
-							self.touched.all();
-						}
+				self.cursor.state.contains(cursor::ORIGIN).into()
+			}
 
-						DEC::Mode::Origin => {
-							self.cursor.state.insert(cursor::ORIGIN);
-							term!(self; cursor Position(Some(0), Some(0)));
-						}
+			25 => {
+				match value {
+					Some(true)  => self.cursor.state.insert(cursor::VISIBLE),
+					Some(false) => self.cursor.state.remove(cursor::VISIBLE),
+					None        => (),
+				}
 
-						DEC::Mode::AutoWrap =>
-							self.mode.insert(mode::WRAP),
+				if value.is_some() {
+					self.touched.push(term!(self; cursor));
+				}
 
-						DEC::Mode::CursorVisible => {
-							self.cursor.state.insert(cursor::VISIBLE);
-							self.touched.push(term!(self; cursor));
-						}
+				self.cursor.state.contains(cursor::VISIBLE).into()
+			}
 
-						DEC::Mode::SmallFont =>
-							actions.push(Action::Resize(132, 24)),
+			69 => {
+				match value {
+					Some(true) =>
+						self.mode.insert(mode::MARGINS),
 
-						mode =>
-							debug!(target: "cancer::terminal::unhandled", "unhandled set: {:?}", mode)
+					Some(false) => {
+						self.mode.remove(mode::MARGINS);
+						self.cursor.margin = (0, self.region.width - 1);
 					}
+
+					None => ()
 				}
+
+				self.mode.contains(mode::MARGINS).into()
 			}
 
-			Control::C1(C1::ControlSequence(CSI::Private(b'h', None, args))) => {
-				debug!(target: "cancer::terminal::mode::set", "set private modes: {:?}", args);
+			9 | 1000 | 1002 | 1003 => {
+				let flag = match id {
+					9    => mode::MOUSE_X10,
+					1000 => mode::MOUSE_BUTTON,
+					1002 => mode::MOUSE_MOTION,
+					_    => mode::MOUSE_MANY,
+				};
 
-				for arg in args.into_iter().flat_map(Option::into_iter) {
-					match arg {
-						1004 =>
-							self.mode.insert(mode::FOCUS),
-
-						2004 =>
-							self.mode.insert(mode::BRACKETED_PASTE),
-
-						9 | 1000 | 1002 | 1003 => {
-							self.mode.remove(mode::MOUSE);
-							self.mode.insert(match arg {
-								9    => mode::MOUSE_X10,
-								1000 => mode::MOUSE_BUTTON,
-								1002 => mode::MOUSE_MOTION,
-								1003 => mode::MOUSE_MANY,
-								_    => unreachable!()
-							});
-						}
+				match value {
+					Some(true) => {
+						self.mode.remove(mode::MOUSE);
+						self.mode.insert(flag);
+					}
 
-						1006 =>
-							self.mode.insert(mode::MOUSE_SGR),
+					Some(false) =>
+						self.mode.remove(mode::MOUSE),
 
-						n =>
-							debug!(target: "cancer::terminal::unhandled", "unhandled set: {}", n)
-					}
+					None => ()
 				}
-			}
-
-			Control::C1(C1::ControlSequence(CSI::Reset(modes))) => {
-				debug!(target: "cancer::terminal::mode::reset", "reset ECMA modes: {:?}", modes);
 
-				for mode in modes {
-					match mode {
-						CSI::Mode::KeyboardLock =>
-							self.mode.remove(mode::KEYBOARD_LOCK),
+				self.mode.contains(flag).into()
+			}
 
-						CSI::Mode::InsertionReplacement =>
-							self.mode.remove(mode::INSERT),
+			1005 | 1006 | 1015 | 1016 => {
+				let flag = match id {
+					1005 => mode::MOUSE_UTF8,
+					1006 => mode::MOUSE_SGR,
+					1015 => mode::MOUSE_URXVT,
+					_    => mode::MOUSE_PIXELS,
+				};
 
-						CSI::Mode::SendReceive =>
-							self.mode.remove(mode::ECHO),
+				match value {
+					Some(true) => {
+						self.mode.remove(mode::MOUSE_ENCODING);
+						self.mode.insert(flag);
+					}
 
-						CSI::Mode::LineFeed =>
-							self.mode.remove(mode::CRLF),
+					Some(false) =>
+						self.mode.remove(flag),
 
-						mode =>
-							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", mode)
-					}
+					None => ()
 				}
+
+				self.mode.contains(flag).into()
 			}
 
-			Control::DEC(DEC::Reset(modes)) => {
-				debug!(target: "cancer::terminal::mode::reset", "reset DEC modes: {:?}", modes);
+			47 | 1047 => {
+				match value {
+					Some(true) =>
+						self.screen(true),
 
-				for mode in modes {
-					match mode {
-						DEC::Mode::ApplicationCursor =>
-							self.mode.remove(mode::APPLICATION_CURSOR),
+					Some(false) if id == 47 =>
+						self.screen(false),
 
-						DEC::Mode::ReverseVideo => {
-							self.mode.remove(mode::REVERSE);
-							self.touched.all();
+					Some(false) => {
+						if self.mode.contains(mode::ALTERNATE) {
+							term!(self; clear);
+							self.screen(false);
 						}
+					}
 
-						DEC::Mode::Origin => {
-							self.cursor.state.remove(cursor::ORIGIN);
-							self.touched.push(term!(self; cursor));
-						}
+					None => ()
+				}
 
-						DEC::Mode::AutoWrap =>
-							self.mode.remove(mode::WRAP),
+				self.mode.contains(mode::ALTERNATE).into()
+			}
 
-						DEC::Mode::CursorVisible => {
-							self.cursor.state.remove(cursor::VISIBLE);
-							self.touched.push(term!(self; cursor));
-						}
+			// The saved cursor has no state to report.
+			1048 => {
+				match value {
+					Some(true)  => self.save(),
+					Some(false) => self.restore(),
+					None        => (),
+				}
 
-						DEC::Mode::SmallFont =>
-							actions.push(Action::Resize(80, 24)),
+				mode::State::Reset
+			}
 
-						mode =>
-							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", mode)
+			1049 => {
+				match value {
+					Some(true) => {
+						if !self.mode.contains(mode::ALTERNATE) {
+							self.save();
+							self.screen(true);
+							term!(self; clear);
+						}
 					}
-				}
-			}
 
-			Control::C1(C1::ControlSequence(CSI::Private(b'l', None, args))) => {
-				debug!(target: "cancer::terminal::mode::reset", "reset private modes: {:?}", args);
+					Some(false) => {
+						if self.mode.contains(mode::ALTERNATE) {
+							self.screen(false);
+							self.restore();
+						}
+					}
 
-				for arg in args.into_iter().flat_map(Option::into_iter) {
-					match arg {
-						1004 =>
-							self.mode.remove(mode::FOCUS),
+					None => ()
+				}
 
-						2004 =>
-							self.mode.remove(mode::BRACKETED_PASTE),
+				self.mode.contains(mode::ALTERNATE).into()
+			}
 
-						9 | 1000 | 1002 | 1003 =>
-							self.mode.remove(mode::MOUSE),
+			_ => {
+				if value.is_some() {
+					debug!(target: "cancer::terminal::unhandled", "unhandled private mode: {} {:?}", id, value);
+				}
 
-						1006 =>
-							self.mode.remove(mode::MOUSE_SGR),
+				mode::State::Unrecognized
+			}
+		}
+	}
 
-						n =>
-							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", n)
-					}
-				}
+	/// Handle a sequence `control` can't parse, returns whether it was handled.
+	fn sequence<O: Write>(&mut self, sequence: &Sequence, mut output: O) -> error::Result<bool> {
+		match (sequence.prefix, sequence.modifier, sequence.id) {
+			// Push keyboard protocol flags.
+			(Some(b'>'), None, b'u') => {
+				self.keyboard.push(keyboard::Flags::from_bits_truncate(sequence.arg(0).unwrap_or(0) as u8));
 			}
 
-			Control::DEC(DEC::ApplicationKeypad(true)) => {
-				self.mode.insert(mode::APPLICATION_KEYPAD);
+			// Pop keyboard protocol flags.
+			(Some(b'<'), None, b'u') => {
+				self.keyboard.pop(sequence.arg(0).unwrap_or(1));
 			}
 
-			Control::DEC(DEC::ApplicationKeypad(false)) => {
-				self.mode.remove(mode::APPLICATION_KEYPAD);
+			// Change the current keyboard protocol flags.
+			(Some(b'='), None, b'u') => {
+				self.keyboard.set(keyboard::Flags::from_bits_truncate(sequence.arg(0).unwrap_or(0) as u8),
+					sequence.arg(1).unwrap_or(1));
 			}
 
-			Control::C1(C1::ControlSequence(CSI::SaveCursor)) |
-			Control::DEC(DEC::SaveCursor) => {
-				self.saved = Some(self.cursor.clone());
+			// Query the current keyboard protocol flags.
+			(Some(b'?'), None, b'u') => {
+				write!(output, "\x1B[?{}u", self.keyboard.flags().bits())?;
 			}
 
-			Control::C1(C1::ControlSequence(CSI::RestoreCursor)) |
-			Control::DEC(DEC::RestoreCursor) => {
-				if let Some(saved) = self.saved.clone() {
-					self.touched.push(term!(self; cursor));
-					self.cursor = saved;
-					self.touched.push(term!(self; cursor));
+			// Change the modifyOtherKeys level, the other resources are not supported.
+			(Some(b'>'), None, b'm') => {
+				if sequence.arg(0) == Some(4) {
+					self.keyboard.set_others(cmp::min(sequence.arg(1).unwrap_or(0), 2) as u8);
 				}
 			}
 
-			// Charset.
-			Control::DEC(DEC::SelectCharset(i, charset)) => {
-				if self.cursor.charsets.len() >= i as usize {
-					self.cursor.charsets[i as usize] = charset;
+			// Reset the modifyOtherKeys level.
+			(Some(b'>'), None, b'n') => {
+				if sequence.arg(0) == Some(4) {
+					self.keyboard.set_others(0);
 				}
 			}
 
-			Control::C0(C0::ShiftIn) => {
-				self.cursor.charset = 0;
+			// Query the modifyOtherKeys level.
+			(Some(b'?'), None, b'm') => {
+				if sequence.arg(0) == Some(4) {
+					write!(output, "\x1B[>4;{}m", self.keyboard.others())?;
+				}
 			}
 
-			Control::C0(C0::ShiftOut) => {
-				self.cursor.charset = 1;
-			}
+			// DECSCA, select the protection of inserted characters.
+			(None, Some(b'"'), b'q') => {
+				if sequence.arg(0) == Some(1) {
+					self.cursor.state.insert(cursor::PROTECTED);
+				}
+				else {
+					self.cursor.state.remove(cursor::PROTECTED);
+				}
 
-			// Movement functions.
-			Control::C0(C0::CarriageReturn) => {
-				term!(self; cursor Position(Some(0), None));
+				let style = **self.cursor.style();
+				self.cursor.update(style);
 			}
 
-			Control::C0(C0::LineFeed) => {
-				if term!(self; cursor Down(1)).is_some() {
-					term!(self; scroll! up 1);
+			// DECSACE, select the extent of attribute changes.
+			(None, Some(b'*'), b'x') => {
+				if sequence.arg(0) == Some(2) {
+					self.mode.insert(mode::RECTANGLE);
+				}
+				else {
+					self.mode.remove(mode::RECTANGLE);
 				}
 			}
 
-			Control::C0(C0::Backspace) => {
-				term!(self; cursor Left(1));
-			}
+			// DECFRA, fill a rectangle with the given character.
+			(None, Some(b'$'), b'x') => {
+				let ch = match sequence.arg(0).and_then(char::from_u32) {
+					Some(ch) if (ch >= ' ' && ch <= '~') || (ch >= '\u{A0}' && ch <= '\u{FF}') => ch,
+					_ => return Ok(true),
+				};
 
-			Control::C1(C1::ControlSequence(CSI::CursorPosition { x, y })) => {
-				term!(self; cursor Position(Some(x), Some(y)));
-			}
+				if let Some((left, top, right, bottom)) = self.rectangle(sequence, 1) {
+					for y in top ..= bottom {
+						term!(self; clean head (left, y));
 
-			Control::C1(C1::ControlSequence(CSI::CursorVerticalPosition(n))) => {
-				term!(self; cursor Position(None, Some(n)));
+						for x in left ..= right {
+							self.grid[(x, y)].make_occupied(ch.to_string(), self.cursor.style().clone());
+							self.touched.mark(x, y);
+						}
+
+						term!(self; clean references (right + 1, y));
+					}
+				}
 			}
 
-			Control::C1(C1::ControlSequence(CSI::CursorHorizontalPosition(n))) => {
-				term!(self; cursor Position(Some(n), None));
+			// DECERA, erase a rectangle.
+			(None, Some(b'$'), b'z') => {
+				if let Some(rectangle) = self.rectangle(sequence, 0) {
+					self.erase(rectangle, false);
+				}
 			}
 
-			Control::C1(C1::ControlSequence(CSI::CursorUp(n))) => {
-				term!(self; cursor Up(n));
+			// DECSERA, erase the unprotected characters in a rectangle.
+			(None, Some(b'$'), b'{') => {
+				if let Some(rectangle) = self.rectangle(sequence, 0) {
+					self.erase(rectangle, true);
+				}
 			}
 
-			Control::C1(C1::ControlSequence(CSI::CursorDown(n))) => {
+			// DECCRA, copy a rectangle, pages are not supported.
+			(None, Some(b'$'), b'v') => {
+				if let Some((left, top, right, bottom)) = self.rectangle(sequence, 0) {
+					let cells = (top ..= bottom).map(|y|
+						(left ..= right).map(|x| self.grid[(x, y)].clone()).collect::<Vec<_>>()).collect::<Vec<_>>();
+
+					// In origin mode the destination is relative to and bound by
+					// the margins.
+					let (x, y, (width, height)) = if self.cursor.state.contains(cursor::ORIGIN) {
+						(self.cursor.margin.0, self.cursor.scroll.0, (self.cursor.margin.1 + 1, self.cursor.scroll.1 + 1))
+					}
+					else {
+						(0, 0, (self.region.width, self.region.height))
+					};
+
+					let x = x.saturating_add(sequence.arg(6).unwrap_or(1).saturating_sub(1));
+					let y = y.saturating_add(sequence.arg(5).unwrap_or(1).saturating_sub(1));
+
+					if x >= width || y >= height {
+						return Ok(true);
+					}
+
+					let end = cmp::min(x + (right - left), width - 1);
+
+					for (row, y) in cells.into_iter().zip(y .. height) {
+						term!(self; clean head (x, y));
+
+						for (cell, x) in row.into_iter().zip(x ..= end) {
+							self.grid[(x, y)] = cell;
+							self.touched.mark(x, y);
+						}
+
+						// Wide characters cut at the edges lose their references.
+						if self.grid[(x, y)].is_reference() {
+							self.grid[(x, y)].make_empty(self.cursor.style().clone());
+						}
+
+						if !self.grid[(end, y)].is_reference() && self.grid[(end, y)].is_wide() {
+							self.grid[(end, y)].make_empty(self.cursor.style().clone());
+						}
+
+						term!(self; clean references (x + 1, y));
+						term!(self; clean references (end + 1, y));
+					}
+				}
+			}
+
+			// DECCARA, change the attributes in a rectangle.
+			(None, Some(b'$'), b'r') => {
+				if let Some(rectangle) = self.rectangle(sequence, 0) {
+					let args = (4 .. cmp::max(sequence.args.len(), 5))
+						.map(|i| sequence.arg(i).unwrap_or(0)).collect::<Vec<_>>();
+
+					// Any kind of underline counts as underlined.
+					self.attributes(rectangle, |attributes| {
+						for &arg in &args {
+							match arg {
+								0  => attributes.remove(style::BOLD | style::UNDERLINES | style::BLINK | style::REVERSE),
+								1  => attributes.insert(style::BOLD),
+								4  => {
+									attributes.remove(style::UNDERLINES);
+									attributes.insert(style::UNDERLINE);
+								}
+
+								5  => attributes.insert(style::BLINK),
+								7  => attributes.insert(style::REVERSE),
+								22 => attributes.remove(style::BOLD),
+								24 => attributes.remove(style::UNDERLINES),
+								25 => attributes.remove(style::BLINK),
+								27 => attributes.remove(style::REVERSE),
+								_  => (),
+							}
+						}
+					});
+				}
+			}
+
+			// DECRARA, reverse the attributes in a rectangle.
+			(None, Some(b'$'), b't') => {
+				if let Some(rectangle) = self.rectangle(sequence, 0) {
+					let args = (4 .. cmp::max(sequence.args.len(), 5))
+						.map(|i| sequence.arg(i).unwrap_or(0)).collect::<Vec<_>>();
+
+					// Any kind of underline counts as underlined, and reversing it
+					// removes them all.
+					fn underline(attributes: &mut style::Attributes) {
+						if attributes.intersects(style::UNDERLINES) {
+							attributes.remove(style::UNDERLINES);
+						}
+						else {
+							attributes.insert(style::UNDERLINE);
+						}
+					}
+
+					self.attributes(rectangle, |attributes| {
+						for &arg in &args {
+							match arg {
+								0 => {
+									attributes.toggle(style::BOLD | style::BLINK | style::REVERSE);
+									underline(attributes);
+								}
+
+								1 => attributes.toggle(style::BOLD),
+								4 => underline(attributes),
+								5 => attributes.toggle(style::BLINK),
+								7 => attributes.toggle(style::REVERSE),
+								_ => (),
+							}
+						}
+					});
+				}
+			}
+
+			// DECRQCRA, report the checksum of a rectangle the way xterm does it.
+			(None, Some(b'*'), b'y') => {
+				let total = if let Some((left, top, right, bottom)) = self.rectangle(sequence, 2) {
+					checksum((top ..= bottom).flat_map(|y| (left ..= right).map(move |x| (x, y)))
+						.map(|(x, y)| &self.grid[(x, y)]))
+				}
+				else {
+					checksum(None)
+				};
+
+				write!(output, "\x1BP{}!~{:04X}\x1B\\", sequence.arg(0).unwrap_or(0), total)?;
+			}
+
+			// XTSMGRAPHICS for the sixel color registers and the sixel and ReGIS
+			// geometry.
+			(Some(b'?'), None, b'S') => {
+				let item   = sequence.arg(0).unwrap_or(0);
+				let action = sequence.arg(1).unwrap_or(0);
+				let width  = self.region.width * self.font.0;
+				let height = self.region.height * self.font.1;
+
+				match (item, action) {
+					(1, 1) | (1, 4) => {
+						write!(output, "\x1B[?1;0;{}S", if action == 1 { self.registers } else { sixel::REGISTERS })?;
+					}
+
+					(1, 2) => {
+						self.registers = sixel::REGISTERS;
+						write!(output, "\x1B[?1;0;{}S", self.registers)?;
+					}
+
+					(1, 3) => {
+						self.registers = util::clamp(sequence.arg(2).unwrap_or(sixel::REGISTERS), 1, sixel::REGISTERS);
+						write!(output, "\x1B[?1;0;{}S", self.registers)?;
+					}
+
+					// The geometry is bound to the window size.
+					(2, 1) | (2, 2) | (2, 4) | (3, 1) | (3, 2) | (3, 4) => {
+						write!(output, "\x1B[?{};0;{};{}S", item, width, height)?;
+					}
+
+					(2, 3) | (3, 3) => {
+						write!(output, "\x1B[?{};0;{};{}S", item,
+							cmp::min(sequence.arg(2).unwrap_or(width), width),
+							cmp::min(sequence.arg(3).unwrap_or(height), height))?;
+					}
+
+					(1, _) | (2, _) | (3, _) => {
+						write!(output, "\x1B[?{};2;0S", item)?;
+					}
+
+					_ => {
+						write!(output, "\x1B[?{};1;0S", item)?;
+					}
+				}
+			}
+
+			// DECSLRM is only available when left and right margins are enabled,
+			// otherwise it's the cursor save.
+			(None, None, b's') if self.mode.contains(mode::MARGINS) => {
+				let left  = sequence.arg(0).unwrap_or(1).saturating_sub(1);
+				let right = match sequence.arg(1) {
+					Some(0) | None => self.region.width,
+					Some(n)        => cmp::min(n, self.region.width),
+				} - 1;
+
+				if left < right {
+					self.cursor.margin = (left, right);
+					term!(self; cursor Position(Some(0), Some(0)));
+				}
+			}
+
+			// DECDC and DECIC, the parser only knows their private form.
+			(None, Some(b'\''), b'~') | (None, Some(b'\''), b'}') => {
+				self.columns(cmp::max(sequence.arg(0).unwrap_or(1), 1), sequence.id == b'}');
+			}
+
+			// SGR, including sub-parameters and extended underlines.
+			(None, None, b'm') => {
+				if let Some(attrs) = sequence::rendition(sequence) {
+					self.rendition(attrs);
+				}
+				else {
+					return Ok(false);
+				}
+			}
+
+			_ =>
+				return Ok(false)
+		}
+
+		Ok(true)
+	}
+
+	fn control<O: Write>(&mut self, control: Control, mut output: O) -> error::Result<Vec<Action>> {
+		let mut actions = Vec::new();
+
+		match control {
+			// Attributes.
+			Control::C1(C1::ControlSequence(CSI::DeviceAttributes(0))) => {
+				output.write_all(b"\033[?64;3;4;6;21c")?;
+			}
+
+			// DECRQM for ECMA modes.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'p', Some(b'$'), args))) => {
+				let id = args.get(0).cloned().and_then(|v| v).unwrap_or(0);
+				write!(output, "\x1B[{};{}$y", id, self.switch(false, id, None, &mut actions) as u8)?;
+			}
+
+			// DECRQM for private modes.
+			Control::C1(C1::ControlSequence(CSI::Private(b'p', Some(b'$'), args))) => {
+				let id = args.get(0).cloned().and_then(|v| v).unwrap_or(0);
+				write!(output, "\x1B[?{};{}$y", id, self.switch(true, id, None, &mut actions) as u8)?;
+			}
+
+			// XTWINOPS.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b't', None, args))) => {
+				actions.extend(self.window(&args, output.by_ref())?);
+			}
+
+			Control::C1(C1::ControlSequence(CSI::DeviceStatusReport(CSI::Report::CursorPosition))) => {
+				control::format_to(output.by_ref(),
+					&CSI::CursorPositionReport { x: self.cursor.x(), y: self.cursor.y() })?;
+			}
+
+			Control::DEC(DEC::Unicode(value)) => {
+				if value {
+					self.mode.insert(mode::UTF8);
+				}
+				else {
+					self.mode.remove(mode::UTF8);
+				}
+			}
+
+			Control::DEC(DEC::ScrollRegion { top, bottom }) => {
+				let mut top    = top;
+				let mut bottom = bottom.unwrap_or(self.region.height);
+
+				top    = util::clamp(top, 0, self.region.height - 1);
+				bottom = util::clamp(bottom, 0, self.region.height - 1);
+
+				if top > bottom {
+					mem::swap(&mut top, &mut bottom);
+				}
+
+				self.cursor.scroll = (top, bottom);
+				term!(self; cursor Position(Some(0), Some(0)));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Set(modes))) => {
+				debug!(target: "cancer::terminal::mode::set", "set ECMA modes: {:?}", modes);
+
+				for mode in modes {
+					self.switch(false, mode.into(), Some(true), &mut actions);
+				}
+			}
+
+			Control::DEC(DEC::Set(modes)) => {
+				debug!(target: "cancer::terminal::mode::set", "set DEC modes: {:?}", modes);
+
+				for mode in modes {
+					self.switch(true, mode.into(), Some(true), &mut actions);
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Private(b'h', None, args))) => {
+				debug!(target: "cancer::terminal::mode::set", "set private modes: {:?}", args);
+
+				for arg in args.into_iter().flat_map(Option::into_iter) {
+					self.switch(true, arg, Some(true), &mut actions);
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Reset(modes))) => {
+				debug!(target: "cancer::terminal::mode::reset", "reset ECMA modes: {:?}", modes);
+
+				for mode in modes {
+					self.switch(false, mode.into(), Some(false), &mut actions);
+				}
+			}
+
+			Control::DEC(DEC::Reset(modes)) => {
+				debug!(target: "cancer::terminal::mode::reset", "reset DEC modes: {:?}", modes);
+
+				for mode in modes {
+					self.switch(true, mode.into(), Some(false), &mut actions);
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Private(b'l', None, args))) => {
+				debug!(target: "cancer::terminal::mode::reset", "reset private modes: {:?}", args);
+
+				for arg in args.into_iter().flat_map(Option::into_iter) {
+					self.switch(true, arg, Some(false), &mut actions);
+				}
+			}
+
+			Control::DEC(DEC::ApplicationKeypad(true)) => {
+				self.mode.insert(mode::APPLICATION_KEYPAD);
+			}
+
+			Control::DEC(DEC::ApplicationKeypad(false)) => {
+				self.mode.remove(mode::APPLICATION_KEYPAD);
+			}
+
+			Control::C1(C1::ControlSequence(CSI::SaveCursor)) |
+			Control::DEC(DEC::SaveCursor) => {
+				self.save();
+			}
+
+			Control::C1(C1::ControlSequence(CSI::RestoreCursor)) |
+			Control::DEC(DEC::RestoreCursor) => {
+				self.restore();
+			}
+
+			Control::DEC(DEC::SoftReset) => {
+				self.soft_reset();
+			}
+
+			Control::DEC(DEC::ResetInitial) => {
+				self.hard_reset();
+				actions.push(Action::Icon(self.icon.clone()));
+			}
+
+			// Charset.
+			Control::DEC(DEC::SelectCharset(i, charset)) => {
+				if self.cursor.charsets.len() >= i as usize {
+					self.cursor.charsets[i as usize] = charset;
+				}
+			}
+
+			Control::C0(C0::ShiftIn) => {
+				self.cursor.charset = 0;
+			}
+
+			Control::C0(C0::ShiftOut) => {
+				self.cursor.charset = 1;
+			}
+
+			Control::C1(C1::SingleShiftTwo) => {
+				self.shift = Some(2);
+			}
+
+			Control::C1(C1::SingleShiftThree) => {
+				self.shift = Some(3);
+			}
+
+			// Movement functions.
+			Control::C0(C0::CarriageReturn) => {
+				let start = self.cursor.start();
+				term!(self; cursor Absolute(Some(start), None));
+			}
+
+			Control::C0(C0::LineFeed) => {
+				if term!(self; cursor Down(1)).is_some() {
+					term!(self; scroll! up 1);
+				}
+			}
+
+			Control::C0(C0::Backspace) => {
+				term!(self; cursor Left(1));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::CursorPosition { x, y })) => {
+				term!(self; cursor Position(Some(x), Some(y)));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::CursorVerticalPosition(n))) => {
+				term!(self; cursor Position(None, Some(n)));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::CursorHorizontalPosition(n))) => {
+				term!(self; cursor Position(Some(n), None));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::CursorUp(n))) => {
+				term!(self; cursor Up(n));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::CursorDown(n))) => {
 				term!(self; cursor Down(n));
 			}
 
@@ -1313,7 +2259,8 @@ impl Terminal {
 					term!(self; scroll up 1);
 				}
 
-				term!(self; cursor Position(Some(0), None));
+				let start = self.cursor.start();
+				term!(self; cursor Absolute(Some(start), None));
 			}
 
 			// Erase functions.
@@ -1364,15 +2311,7 @@ impl Terminal {
 			}
 
 			Control::C1(C1::ControlSequence(CSI::EraseDisplay(CSI::Erase::All))) => {
-				for y in 0 .. self.region.height {
-					for x in 0 .. self.region.width {
-						self.grid[(x, y)].make_empty(self.cursor.style().clone());
-					}
-
-					self.grid.wrapped(y, false);
-				}
-
-				self.touched.all();
+				term!(self; clear);
 			}
 
 			Control::C1(C1::ControlSequence(CSI::EraseLine(CSI::Erase::ToEnd))) => {
@@ -1430,41 +2369,85 @@ impl Terminal {
 			}
 
 			Control::C1(C1::ControlSequence(CSI::DeleteLine(n))) => {
-				term!(self; scroll up n from self.cursor.y());
+				let (left, right) = self.cursor.margin();
+
+				if self.cursor.x() >= left && self.cursor.x() <= right {
+					term!(self; scroll up n from self.cursor.y());
+				}
 			}
 
 			Control::C1(C1::ControlSequence(CSI::DeleteCharacter(n))) => {
-				let (x, y) = term!(self; cursor);
-				self.grid.delete(x, y, n);
+				let (left, right) = self.cursor.margin();
+				let (x, y)        = term!(self; cursor);
 
-				for x in x .. self.region.width {
-					self.touched.mark(x, y);
+				if x >= left && x <= right {
+					self.grid.delete(x, y, n, self.cursor.end());
+
+					for x in x .. self.region.width {
+						self.touched.mark(x, y);
+					}
 				}
 			}
 
+			Control::DEC(DEC::DeleteColumn(n)) => {
+				self.columns(n, false);
+			}
+
 			// Insertion functions.
 			Control::DEC(DEC::AlignmentTest) => {
 				for (x, y) in self.region.absolute() {
 					self.grid[(x, y)].make_occupied("E", self.cursor.style().clone());
 				}
 
+				for y in 0 .. self.region.height {
+					self.grid.size(y, Size::Single);
+				}
+
+				term!(self; line);
 				self.touched.all();
 			}
 
-			Control::C1(C1::ControlSequence(CSI::InsertLine(n))) => {
-				term!(self; scroll down n from self.cursor.y());
+			Control::DEC(DEC::Double(DEC::Half::Top)) => {
+				self.resize_line(Size::Top);
 			}
 
-			Control::C1(C1::ControlSequence(CSI::InsertCharacter(n))) => {
-				let (x, y) = term!(self; cursor);
+			Control::DEC(DEC::Double(DEC::Half::Bottom)) => {
+				self.resize_line(Size::Bottom);
+			}
 
-				self.grid.insert(x, y, n);
+			Control::DEC(DEC::DoubleWidth) => {
+				self.resize_line(Size::Wide);
+			}
 
-				for x in x .. self.region.width {
-					self.touched.mark(x, y);
+			Control::DEC(DEC::SingleWidth) => {
+				self.resize_line(Size::Single);
+			}
+
+			Control::C1(C1::ControlSequence(CSI::InsertLine(n))) => {
+				let (left, right) = self.cursor.margin();
+
+				if self.cursor.x() >= left && self.cursor.x() <= right {
+					term!(self; scroll down n from self.cursor.y());
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::InsertCharacter(n))) => {
+				let (left, right) = self.cursor.margin();
+				let (x, y)        = term!(self; cursor);
+
+				if x >= left && x <= right {
+					self.grid.insert(x, y, n, self.cursor.end());
+
+					for x in x .. self.region.width {
+						self.touched.mark(x, y);
+					}
 				}
 			}
 
+			Control::DEC(DEC::InsertColumn(n)) => {
+				self.columns(n, true);
+			}
+
 			Control::C0(C0::HorizontalTabulation) => {
 				term!(self; tab 1);
 			}
@@ -1493,144 +2476,7 @@ impl Terminal {
 
 			// Style functions.
 			Control::C1(C1::ControlSequence(CSI::SelectGraphicalRendition(attrs))) => {
-				fn to_rgba(color: &SGR::Color) -> Rgba<f64> {
-					match *color {
-						SGR::Color::Transparent =>
-							Rgba::new(0.0, 0.0, 0.0, 0.0),
-
-						SGR::Color::Rgb(r, g, b) =>
-							Rgba::new_u8(r, g, b, 255),
-
-						SGR::Color::Cmy(c, m, y) => {
-							let c = c as f64 / 255.0;
-							let m = m as f64 / 255.0;
-							let y = y as f64 / 255.0;
-
-							Rgba::new(
-								1.0 - c,
-								1.0 - m,
-								1.0 - y,
-								1.0)
-						}
-
-						SGR::Color::Cmyk(c, m, y, k) => {
-							let c = c as f64 / 255.0;
-							let m = m as f64 / 255.0;
-							let y = y as f64 / 255.0;
-							let k = k as f64 / 255.0;
-
-							Rgba::new(
-								1.0 - (c * (1.0 - k) + k),
-								1.0 - (m * (1.0 - k) + k),
-								1.0 - (y * (1.0 - k) + k),
-								1.0)
-						}
-
-						_ => unreachable!()
-					}
-				}
-
-				let mut style = **self.cursor.style();
-
-				for mut attr in attrs {
-					if self.config.style().bold().is_bright() {
-						match attr {
-							SGR::Foreground(SGR::Color::Index(ref mut n)) if *n < 8 => {
-								self.cursor.bright = Some(*n);
-
-								if style.attributes.contains(style::BOLD) {
-									*n += 8;
-								}
-							}
-
-							SGR::Reset | SGR::Foreground(_) => {
-								self.cursor.bright = None
-							}
-
-							SGR::Font(SGR::Weight::Normal) | SGR::Font(SGR::Weight::Faint) => {
-								if let Some(n) = self.cursor.bright {
-									style.foreground = Some(*self.config.color().get(n));
-								}
-							}
-
-							SGR::Font(SGR::Weight::Bold) => {
-								if let Some(n) = self.cursor.bright {
-									style.foreground = Some(*self.config.color().get(n + 8));
-								}
-							}
-
-							_ => ()
-						}
-					}
-
-					match attr {
-						SGR::Reset =>
-							style = Style::default(),
-
-						SGR::Italic(true) =>
-							style.attributes.insert(style::ITALIC),
-						SGR::Italic(false) =>
-							style.attributes.remove(style::ITALIC),
-
-						SGR::Underline(true) =>
-							style.attributes.insert(style::UNDERLINE),
-						SGR::Underline(false) =>
-							style.attributes.remove(style::UNDERLINE),
-
-						SGR::Blink(true) =>
-							style.attributes.insert(style::BLINK),
-						SGR::Blink(false) =>
-							style.attributes.remove(style::BLINK),
-
-						SGR::Reverse(true) =>
-							style.attributes.insert(style::REVERSE),
-						SGR::Reverse(false) =>
-							style.attributes.remove(style::REVERSE),
-
-						SGR::Invisible(true) =>
-							style.attributes.insert(style::INVISIBLE),
-						SGR::Invisible(false) =>
-							style.attributes.remove(style::INVISIBLE),
-
-						SGR::Struck(true) =>
-							style.attributes.insert(style::STRUCK),
-						SGR::Struck(false) =>
-							style.attributes.remove(style::STRUCK),
-
-						SGR::Font(SGR::Weight::Normal) =>
-							style.attributes.remove(style::BOLD | style::FAINT),
-
-						SGR::Font(SGR::Weight::Bold) => {
-							style.attributes.remove(style::FAINT);
-							style.attributes.insert(style::BOLD);
-						}
-
-						SGR::Font(SGR::Weight::Faint) => {
-							style.attributes.remove(style::BOLD);
-							style.attributes.insert(style::FAINT);
-						}
-
-						SGR::Foreground(SGR::Color::Default) =>
-							style.foreground = Some(*self.config.style().color().foreground()),
-
-						SGR::Foreground(SGR::Color::Index(n)) =>
-							style.foreground = Some(*self.config.color().get(n)),
-
-						SGR::Foreground(ref color) =>
-							style.foreground = Some(to_rgba(color)),
-
-						SGR::Background(SGR::Color::Default) =>
-							style.background = Some(*self.config.style().color().background()),
-
-						SGR::Background(SGR::Color::Index(n)) =>
-							style.background = Some(*self.config.color().get(n)),
-
-						SGR::Background(ref color) =>
-							style.background = Some(to_rgba(color)),
-					}
-				}
-
-				self.cursor.update(style);
+				self.rendition(attrs.into_iter().map(Rendition::Standard));
 			}
 
 			Control::DEC(DEC::CursorStyle(n)) => {
@@ -1690,9 +2536,12 @@ impl Terminal {
 				self.command = Some(Command::Device);
 			}
 
+			Control::C1(C1::ApplicationProgramCommand) => {
+				self.command = Some(Command::Application);
+			}
+
 			Control::C1(C1::String) |
 			Control::C1(C1::PrivacyMessage) |
-			Control::C1(C1::ApplicationProgramCommand) |
 			Control::C1(C1::SingleCharacter) |
 			Control::C1(C1::OperatingSystemCommand) => {
 				self.command = Some(Command::Internal);
@@ -1706,58 +2555,284 @@ impl Terminal {
 		Ok(actions)
 	}
 
+	/// Change the size of the line under the cursor.
+	fn resize_line(&mut self, size: Size) {
+		let (_, y) = term!(self; cursor);
+
+		// The cells past the halved width are lost.
+		if size != Size::Single {
+			let half = cmp::max(self.region.width / 2, 1);
+
+			if half < self.region.width {
+				if self.grid[(half, y)].is_reference() {
+					let offset = self.grid[(half, y)].offset();
+					self.grid[(half - offset, y)].make_empty(self.cursor.style().clone());
+				}
+
+				for x in half .. self.region.width {
+					self.grid[(x, y)].make_empty(self.cursor.style().clone());
+				}
+			}
+		}
+
+		self.grid.size(y, size);
+		self.touched.line(y);
+		term!(self; line);
+	}
+
+	/// Insert or delete columns at the cursor within the scrolling region and
+	/// margins (DECIC and DECDC).
+	fn columns(&mut self, n: u32, insert: bool) {
+		let (left, right) = self.cursor.margin();
+		let (top, bottom) = self.cursor.scroll();
+		let (x, _)        = term!(self; cursor);
+
+		if x >= left && x <= right {
+			for y in top ..= bottom {
+				if insert {
+					self.grid.insert(x, y, n, right);
+				}
+				else {
+					self.grid.delete(x, y, n, right);
+				}
+
+				self.touched.line(y);
+			}
+		}
+	}
+
+	/// Apply the graphic renditions to the cursor style.
+	fn rendition<I: IntoIterator<Item = Rendition>>(&mut self, attrs: I) {
+		fn to_rgba(color: &SGR::Color) -> Rgba<f64> {
+			match *color {
+				SGR::Color::Transparent =>
+					Rgba::new(0.0, 0.0, 0.0, 0.0),
+
+				SGR::Color::Rgb(r, g, b) =>
+					Rgba::new_u8(r, g, b, 255),
+
+				SGR::Color::Cmy(c, m, y) => {
+					let c = c as f64 / 255.0;
+					let m = m as f64 / 255.0;
+					let y = y as f64 / 255.0;
+
+					Rgba::new(
+						1.0 - c,
+						1.0 - m,
+						1.0 - y,
+						1.0)
+				}
+
+				SGR::Color::Cmyk(c, m, y, k) => {
+					let c = c as f64 / 255.0;
+					let m = m as f64 / 255.0;
+					let y = y as f64 / 255.0;
+					let k = k as f64 / 255.0;
+
+					Rgba::new(
+						1.0 - (c * (1.0 - k) + k),
+						1.0 - (m * (1.0 - k) + k),
+						1.0 - (y * (1.0 - k) + k),
+						1.0)
+				}
+
+				_ => unreachable!()
+			}
+		}
+
+		let mut style = **self.cursor.style();
+
+		for attr in attrs {
+			let mut attr = match attr {
+				Rendition::Standard(attr) =>
+					attr,
+
+				Rendition::Underline(kind) => {
+					style.attributes.remove(style::UNDERLINES);
+					style.attributes.insert(kind);
+					continue;
+				}
+
+				Rendition::Decoration(SGR::Color::Default) => {
+					style.underline  = None;
+					style.palette[2] = None;
+					continue;
+				}
+
+				Rendition::Decoration(SGR::Color::Index(n)) => {
+					style.underline  = Some(*self.config.color().get(n));
+					style.palette[2] = Some(n);
+					continue;
+				}
+
+				Rendition::Decoration(ref color) => {
+					style.underline  = Some(to_rgba(color));
+					style.palette[2] = None;
+					continue;
+				}
+
+				Rendition::Overline(value) => {
+					if value {
+						style.attributes.insert(style::OVERLINE);
+					}
+					else {
+						style.attributes.remove(style::OVERLINE);
+					}
+
+					continue;
+				}
+			};
+
+			if self.config.style().bold().is_bright() {
+				match attr {
+					SGR::Foreground(SGR::Color::Index(ref mut n)) if *n < 8 => {
+						self.cursor.bright = Some(*n);
+
+						if style.attributes.contains(style::BOLD) {
+							*n += 8;
+						}
+					}
+
+					SGR::Reset | SGR::Foreground(_) => {
+						self.cursor.bright = None
+					}
+
+					SGR::Font(SGR::Weight::Normal) | SGR::Font(SGR::Weight::Faint) => {
+						if let Some(n) = self.cursor.bright {
+							style.foreground = Some(*self.config.color().get(n));
+							style.palette[0] = Some(n);
+						}
+					}
+
+					SGR::Font(SGR::Weight::Bold) => {
+						if let Some(n) = self.cursor.bright {
+							style.foreground = Some(*self.config.color().get(n + 8));
+							style.palette[0] = Some(n + 8);
+						}
+					}
+
+					_ => ()
+				}
+			}
+
+			// Keep track of the palette indices, so palette changes recolor
+			// existing cells and DECRQSS can report them back.
+			match attr {
+				SGR::Foreground(ref color) =>
+					style.palette[0] = if let SGR::Color::Index(n) = *color { Some(n) } else { None },
+
+				SGR::Background(ref color) =>
+					style.palette[1] = if let SGR::Color::Index(n) = *color { Some(n) } else { None },
+
+				_ => ()
+			}
+
+			match attr {
+				SGR::Reset =>
+					style = Style::default(),
+
+				SGR::Italic(true) =>
+					style.attributes.insert(style::ITALIC),
+				SGR::Italic(false) =>
+					style.attributes.remove(style::ITALIC),
+
+				SGR::Underline(true) => {
+					style.attributes.remove(style::UNDERLINES);
+					style.attributes.insert(style::UNDERLINE);
+				}
+
+				SGR::Underline(false) =>
+					style.attributes.remove(style::UNDERLINES),
+
+				SGR::Blink(true) =>
+					style.attributes.insert(style::BLINK),
+				SGR::Blink(false) =>
+					style.attributes.remove(style::BLINK),
+
+				SGR::Reverse(true) =>
+					style.attributes.insert(style::REVERSE),
+				SGR::Reverse(false) =>
+					style.attributes.remove(style::REVERSE),
+
+				SGR::Invisible(true) =>
+					style.attributes.insert(style::INVISIBLE),
+				SGR::Invisible(false) =>
+					style.attributes.remove(style::INVISIBLE),
+
+				SGR::Struck(true) =>
+					style.attributes.insert(style::STRUCK),
+				SGR::Struck(false) =>
+					style.attributes.remove(style::STRUCK),
+
+				SGR::Font(SGR::Weight::Normal) =>
+					style.attributes.remove(style::BOLD | style::FAINT),
+
+				SGR::Font(SGR::Weight::Bold) => {
+					style.attributes.remove(style::FAINT);
+					style.attributes.insert(style::BOLD);
+				}
+
+				SGR::Font(SGR::Weight::Faint) => {
+					style.attributes.remove(style::BOLD);
+					style.attributes.insert(style::FAINT);
+				}
+
+				SGR::Foreground(SGR::Color::Default) =>
+					style.foreground = Some(*self.config.style().color().foreground()),
+
+				SGR::Foreground(SGR::Color::Index(n)) =>
+					style.foreground = Some(*self.config.color().get(n)),
+
+				SGR::Foreground(ref color) =>
+					style.foreground = Some(to_rgba(color)),
+
+				SGR::Background(SGR::Color::Default) =>
+					style.background = Some(*self.config.style().color().background()),
+
+				SGR::Background(SGR::Color::Index(n)) =>
+					style.background = Some(*self.config.color().get(n)),
+
+				SGR::Background(ref color) =>
+					style.background = Some(to_rgba(color)),
+			}
+		}
+
+		self.cursor.update(style);
+	}
+
 	fn insert<T: AsRef<str>>(&mut self, ch: T) {
-		let mut ch = ch.as_ref();
-
-		// Convert from normal characters to graphical characters if the charset is
-		// enabled.
-		if term!(self; charset) == DEC::Charset::DEC(DEC::charset::DEC::Graphic) {
-			ch = match ch {
-				"A" => "↑",
-				"B" => "↓",
-				"C" => "→",
-				"D" => "←",
-				"E" => "█",
-				"F" => "▚",
-				"G" => "☃",
-				"_" => " ",
-				"`" => "◆",
-				"a" => "▒",
-				"b" => "␉",
-				"c" => "␌",
-				"d" => "␍",
-				"e" => "␊",
-				"f" => "°",
-				"g" => "±",
-				"h" => "␤",
-				"i" => "␋",
-				"j" => "┘",
-				"k" => "┐",
-				"l" => "┌",
-				"m" => "└",
-				"n" => "┼",
-				"o" => "⎺",
-				"p" => "⎻",
-				"q" => "─",
-				"r" => "⎼",
-				"s" => "⎽",
-				"t" => "├",
-				"u" => "┤",
-				"v" => "┴",
-				"w" => "┬",
-				"x" => "│",
-				"y" => "≤",
-				"z" => "≥",
-				"{" => "π",
-				"|" => "≠",
-				"}" => "£",
-				"~" => "·",
-				_   => ch,
+		let mut buffer = [0; 4];
+		let mut ch     = ch.as_ref();
+
+		// Translate through the selected charset, single shifts only apply to
+		// the next character and GR is only mapped outside of UTF-8 mode.
+		let     shift = self.shift.take();
+		let mut chars = ch.chars();
+
+		if let (Some(c), None) = (chars.next(), chars.next()) {
+			let translated = if c < '\u{80}' {
+				let set = shift.map(|i| self.cursor.charsets[i as usize])
+					.unwrap_or(term!(self; charset));
+
+				charset::translate(set, self.mode.contains(mode::NRCS), c)
+			}
+			else if !self.mode.contains(mode::UTF8) {
+				charset::upper(self.cursor.charsets[self.cursor.right as usize], c)
+			}
+			else {
+				None
 			};
+
+			if let Some(c) = translated {
+				ch = c.encode_utf8(&mut buffer);
+			}
 		}
 
 		let width = ch.width() as u32;
 
+		// The line under the cursor may have changed size through scrolling.
+		term!(self; line);
+
 		// Bail out if it cannot be displayed.
 		if width == 0 {
 			return;
@@ -1769,16 +2844,18 @@ impl Terminal {
 				term!(self; scroll! up 1);
 			}
 
-			term!(self; cursor Position(Some(0), None));
+			let start = self.cursor.start();
+			term!(self; cursor Absolute(Some(start), None));
 			let (_, y) = term!(self; cursor);
 			self.grid.wrapped(y, true);
 		}
 
 		let (x, y) = term!(self; cursor);
+		let edge   = self.cursor.end() + 1;
 
-		// If the character width goes beyond the terminal width, make the cells empty.
-		if x + width > self.region.width {
-			for x in x .. self.region.width {
+		// If the character width goes beyond the right edge, make the cells empty.
+		if x + width > edge {
+			for x in x .. edge {
 				self.grid[(x, y)].make_empty(self.cursor.style().clone());
 				self.touched.mark(x, y);
 			}
@@ -1795,6 +2872,7 @@ impl Terminal {
 		// Just insert the grapheme.
 		else {
 			self.grid[(x, y)].make_occupied(ch, self.cursor.style().clone());
+			self.grid[(x, y)].set_link(self.link.clone());
 			self.touched.mark(x, y);
 
 			for (i, x) in (x + 1 .. x + width).enumerate() {
@@ -1805,7 +2883,7 @@ impl Terminal {
 		}
 
 		// If the character overflows the region, mark it for wrapping.
-		if x + width >= self.region.width {
+		if x + width >= edge {
 			self.cursor.state.insert(cursor::WRAP);
 		}
 		else {
@@ -1813,19 +2891,585 @@ impl Terminal {
 		}
 	}
 
-	fn command(&mut self, command: &str) -> Vec<Action> {
+	/// Handle a device control string.
+	fn device<O: Write>(&mut self, string: &str, mut output: O) -> error::Result<()> {
+		// XTGETTCAP, answered from the installed terminfo.
+		if string.starts_with("+q") {
+			let term = self.config.environment().term().unwrap_or("cancer-256color");
+
+			for name in string[2..].split(';') {
+				let capability = util::hex::decode(name)
+					.and_then(|n| String::from_utf8(n).ok())
+					.and_then(|n| match &*n {
+						"TN" | "name" =>
+							Some(terminfo::Capability::String(term.as_bytes().to_vec())),
+
+						// Terminfo names take precedence over clashing termcap ones.
+						n =>
+							terminfo::get(term, n).or_else(||
+								terminfo::alias(n).and_then(|n| terminfo::get(term, n))),
+					});
+
+				match capability {
+					Some(terminfo::Capability::Boolean) =>
+						write!(output, "\x1BP1+r{}\x1B\\", name)?,
+
+					Some(terminfo::Capability::Number(value)) =>
+						write!(output, "\x1BP1+r{}={}\x1B\\", name, util::hex::encode(value.to_string()))?,
+
+					Some(terminfo::Capability::String(value)) =>
+						write!(output, "\x1BP1+r{}={}\x1B\\", name, util::hex::encode(value))?,
+
+					None =>
+						write!(output, "\x1BP0+r{}\x1B\\", name)?,
+				}
+			}
+		}
+		// DECRQSS.
+		else if string.starts_with("$q") {
+			let setting = match &string[2..] {
+				"m" => {
+					let style = self.cursor.style();
+					let mut sgr = String::from("0");
+
+					for &(attribute, id) in &[
+						(style::BOLD, "1"), (style::FAINT, "2"), (style::ITALIC, "3"), (style::UNDERLINE, "4"),
+						(style::BLINK, "5"), (style::REVERSE, "7"), (style::INVISIBLE, "8"), (style::STRUCK, "9"),
+						(style::DOUBLE, "4:2"), (style::CURLY, "4:3"), (style::DOTTED, "4:4"), (style::DASHED, "4:5"),
+						(style::OVERLINE, "53")]
+					{
+						if style.attributes().contains(attribute) {
+							sgr.push_str(&format!(";{}", id));
+						}
+					}
+
+					let colors = style.palette;
+
+					if let Some(n) = colors[0] {
+						sgr.push_str(&format!(";38;5;{}", n));
+					}
+					else if let Some(color) = style.foreground() {
+						sgr.push_str(&format!(";38;2;{};{};{}",
+							(color.red * 255.0) as u8, (color.green * 255.0) as u8, (color.blue * 255.0) as u8));
+					}
+
+					if let Some(n) = colors[1] {
+						sgr.push_str(&format!(";48;5;{}", n));
+					}
+					else if let Some(color) = style.background() {
+						sgr.push_str(&format!(";48;2;{};{};{}",
+							(color.red * 255.0) as u8, (color.green * 255.0) as u8, (color.blue * 255.0) as u8));
+					}
+
+					if let Some(n) = colors[2] {
+						sgr.push_str(&format!(";58:5:{}", n));
+					}
+					else if let Some(color) = style.underline() {
+						sgr.push_str(&format!(";58:2::{}:{}:{}",
+							(color.red * 255.0) as u8, (color.green * 255.0) as u8, (color.blue * 255.0) as u8));
+					}
+
+					Some(format!("{}m", sgr))
+				}
+
+				"r" =>
+					Some(format!("{};{}r", self.cursor.scroll.0 + 1, self.cursor.scroll.1 + 1)),
+
+				"s" =>
+					Some(format!("{};{}s", self.cursor.margin.0 + 1, self.cursor.margin.1 + 1)),
+
+				" q" => {
+					let shape = match self.cursor.shape() {
+						Shape::Block => 1,
+						Shape::Line  => 3,
+						Shape::Beam  => 5,
+					};
+
+					Some(format!("{} q", if self.cursor.blink() { shape } else { shape + 1 }))
+				}
+
+				"\"p" =>
+					Some(String::from("64;1\"p")),
+
+				_ =>
+					None
+			};
+
+			if let Some(setting) = setting {
+				write!(output, "\x1BP1$r{}\x1B\\", setting)?;
+			}
+			else {
+				write!(output, "\x1BP0$r\x1B\\")?;
+			}
+		}
+		else {
+			debug!(target: "cancer::terminal::unhandled", "unhandled device string: {:?}", string);
+		}
+
+		Ok(())
+	}
+
+	/// Move the rows of image cells into the grid, starting at the cursor,
+	/// optionally scrolling when reaching the bottom.
+	fn image(&mut self, rows: Vec<Vec<cairo::Image>>, edge: u32, scroll: bool) {
+		let count = rows.len();
+
+		for (i, row) in rows.into_iter().enumerate() {
+			let (x, y) = term!(self; cursor);
+
+			for (offset, buffer) in row.into_iter().enumerate() {
+				self.grid[(x + offset as u32, y)].make_image(buffer, self.cursor.style().clone());
+				term!(self; cursor Right(1));
+			}
+
+			// Clean leftover references.
+			let (x, y) = term!(self; cursor);
+			self.grid.clean_references(x - 1, y);
+
+			// If it's the last row, skip cursor movement.
+			if i < count - 1 {
+				if term!(self; cursor Down(1)).is_some() {
+					if !scroll {
+						break;
+					}
+
+					term!(self; scroll! up 1);
+				}
+
+				term!(self; cursor Absolute(Some(edge), None));
+			}
+		}
+	}
+
+	/// Handle an application program command.
+	fn application<O: Write>(&mut self, string: &str, output: O) -> error::Result<()> {
+		if string.starts_with('G') {
+			if let Some((control, payload)) = graphics::parse(&string[1..]) {
+				debug!(target: "cancer::terminal::graphics", "graphics {:?}", control);
+				self.graphic(control, payload, output)?;
+			}
+		}
+		else {
+			debug!(target: "cancer::terminal::unhandled", "unhandled application command: {:?}", string);
+		}
+
+		Ok(())
+	}
+
+	/// Handle a kitty graphics command.
+	fn graphic<O: Write>(&mut self, control: graphics::Control, payload: &str, mut output: O) -> error::Result<()> {
+		let (control, result) = match control.action {
+			b't' | b'T' | b'q' => match self.graphics.transmit(control, payload, self.config.input().files()) {
+				Some((control, Ok(id))) if control.action == b'T' => {
+					let result = self.place(id, &control).map(|_| id);
+					(control, result)
+				}
+
+				Some(response) =>
+					response,
+
+				None =>
+					return Ok(()),
+			},
+
+			b'p' => {
+				let id = if control.id != 0 {
+					Some(control.id)
+				}
+				else {
+					self.graphics.number(control.number)
+				};
+
+				let result = id.ok_or(graphics::Error::NotFound("image not found"))
+					.and_then(|id| self.place(id, &control).map(|_| id));
+
+				(control, result)
+			}
+
+			b'd' => {
+				self.graphics.delete(&control, term!(self; cursor));
+				self.touched.all();
+
+				return Ok(());
+			}
+
+			_ =>
+				(control, Err(graphics::Error::Invalid("unsupported action"))),
+		};
+
+		if let Err(ref error) = result {
+			debug!(target: "cancer::terminal::graphics", "graphics error: {}", error);
+		}
+
+		// Only commands with an id get a response, unless asked to be quiet.
+		if control.id == 0 && control.number == 0 {
+			return Ok(());
+		}
+
+		match result {
+			Ok(_) if control.quiet >= 1 => return Ok(()),
+			Err(_) if control.quiet >= 2 => return Ok(()),
+			_ => ()
+		}
+
+		let mut keys = Vec::new();
+
+		match result {
+			Ok(id) if id != 0 =>
+				keys.push(format!("i={}", id)),
+
+			Err(_) if control.id != 0 =>
+				keys.push(format!("i={}", control.id)),
+
+			_ => ()
+		}
+
+		if control.number != 0 {
+			keys.push(format!("I={}", control.number));
+		}
+
+		if control.placement != 0 {
+			keys.push(format!("p={}", control.placement));
+		}
+
+		match result {
+			Ok(_) =>
+				write!(output, "\x1B_G{};OK\x1B\\", keys.join(","))?,
+
+			Err(error) =>
+				write!(output, "\x1B_G{};{}\x1B\\", keys.join(","), error)?,
+		}
+
+		Ok(())
+	}
+
+	/// Place a stored image at the cursor.
+	fn place(&mut self, id: u32, control: &graphics::Control) -> Result<(), graphics::Error> {
+		let (x, y)          = term!(self; cursor);
+		let (columns, rows) = self.graphics.place(id, control, (x, y), self.font)?;
+
+		self.touched.all();
+
+		// Move the cursor past the image, unless asked not to.
+		if control.cursor == 0 {
+			for _ in 1 .. rows {
+				if term!(self; cursor Down(1)).is_some() {
+					term!(self; scroll! up 1);
+				}
+			}
+
+			term!(self; cursor Absolute(Some(x + columns), None));
+		}
+
+		Ok(())
+	}
+
+	/// Handle a window manipulation request (XTWINOPS), every operation has to
+	/// be allowed in the configuration.
+	fn window<O: Write>(&mut self, args: &[Option<u32>], mut output: O) -> error::Result<Vec<Action>> {
+		let mut actions = Vec::new();
+		let     config  = self.config.clone();
+		let     allows  = |name: &str| config.environment().allows(name);
+		let     arg     = |index: usize| args.get(index).cloned().and_then(|v| v);
+
+		match arg(0).unwrap_or(0) {
+			id @ 1 | id @ 2 if allows("iconify") =>
+				actions.push(Action::Iconify(id == 2)),
+
+			id @ 5 | id @ 6 if allows("raise") =>
+				actions.push(Action::Raise(id == 5)),
+
+			7 => {
+				self.touched.all();
+			}
+
+			4 if allows("resize") => {
+				let rows    = arg(1).filter(|&n| n != 0).map(|n| n / self.font.1).unwrap_or(self.region.height);
+				let columns = arg(2).filter(|&n| n != 0).map(|n| n / self.font.0).unwrap_or(self.region.width);
+
+				actions.push(Action::Resize(cmp::max(columns, 1), cmp::max(rows, 1)));
+			}
+
+			8 if allows("resize") => {
+				let rows    = arg(1).filter(|&n| n != 0).unwrap_or(self.region.height);
+				let columns = arg(2).filter(|&n| n != 0).unwrap_or(self.region.width);
+
+				actions.push(Action::Resize(columns, rows));
+			}
+
+			9 if allows("maximize") =>
+				actions.push(Action::Maximize(arg(1).unwrap_or(0) != 0)),
+
+			11 if allows("report") =>
+				output.write_all(b"\x1B[1t")?,
+
+			14 if allows("report") =>
+				write!(output, "\x1B[4;{};{}t", self.region.height * self.font.1, self.region.width * self.font.0)?,
+
+			16 if allows("report") =>
+				write!(output, "\x1B[6;{};{}t", self.font.1, self.font.0)?,
+
+			18 if allows("report") =>
+				write!(output, "\x1B[8;{};{}t", self.region.height, self.region.width)?,
+
+			19 if allows("report") =>
+				write!(output, "\x1B[9;{};{}t", self.screen.1 / self.font.1, self.screen.0 / self.font.0)?,
+
+			20 if allows("title") =>
+				write!(output, "\x1B]L{}\x1B\\", self.icon)?,
+
+			21 if allows("title") =>
+				write!(output, "\x1B]l{}\x1B\\", self.title)?,
+
+			// The second parameter selects the icon name (1), the title (2) or
+			// both (0).
+			22 if allows("stack") => {
+				let which = arg(1).unwrap_or(0);
+
+				if self.titles.len() >= TITLES {
+					self.titles.remove(0);
+				}
+
+				self.titles.push((
+					Some(self.icon.clone()).filter(|_| which == 0 || which == 1),
+					Some(self.title.clone()).filter(|_| which == 0 || which == 2)));
+			}
+
+			23 if allows("stack") => {
+				let which = arg(1).unwrap_or(0);
+
+				if let Some((icon, title)) = self.titles.pop() {
+					if let Some(icon) = icon.filter(|_| which == 0 || which == 1) {
+						self.icon = icon;
+						actions.push(Action::Icon(self.icon.clone()));
+					}
+
+					if let Some(title) = title.filter(|_| which == 0 || which == 2) {
+						self.title = title;
+					}
+				}
+			}
+
+			rows if rows >= 24 && allows("resize") =>
+				actions.push(Action::Resize(self.region.width, rows)),
+
+			id =>
+				debug!(target: "cancer::terminal::unhandled", "unhandled window operation: {} {:?}", id, args),
+		}
+
+		Ok(actions)
+	}
+
+	fn command<O: Write>(&mut self, command: &str, mut output: O) -> error::Result<Vec<Action>> {
 		let mut actions = Vec::new();
 
-		match command {
-			cmd if cmd.starts_with("0;") ||
-			       cmd.starts_with("1;") ||
-			       cmd.starts_with("2;") ||
-			       cmd.starts_with("k;") => {
-				actions.push(Action::Title(String::from(&cmd[2..])));
+		// Dispatch on the whole identifier, `104` must not match `1049`.
+		let (id, args) = match command.find(';') {
+			Some(index) => (&command[.. index], &command[index + 1 ..]),
+			None        => (command, ""),
+		};
+
+		match id {
+			"0" => {
+				self.title = String::from(args);
+				self.icon  = self.title.clone();
+				actions.push(Action::Icon(self.icon.clone()));
+			}
+
+			"1" => {
+				self.icon = String::from(args);
+				actions.push(Action::Icon(self.icon.clone()));
+			}
+
+			"2" | "k" => {
+				self.title = String::from(args);
+			}
+
+			"1337" if args.starts_with("File=") => {
+				if let Some(file) = Inline::parse(&args[5..]) {
+					if file.is_inline() {
+						let (x, _) = term!(self; cursor);
+						let limit  = (self.region.width - x, self.region.height);
+
+						if let Some(rows) = file.cells(self.font, limit) {
+							self.image(rows, x, true);
+						}
+					}
+					else {
+						// Only saved when the user asks for it.
+						self.download = Some((file.name().map(String::from), file.into_inner()));
+						actions.push(Action::Urgent);
+					}
+				}
+			}
+
+			"52" => {
+				let mut parts = args.splitn(2, ';');
+				let     names = parts.next().unwrap_or("");
+				let     data  = parts.next().unwrap_or("");
+
+				// Like xterm an empty list means `s0`, the cut buffers are not
+				// supported.
+				let mut selections = Vec::new();
+
+				for name in if names.is_empty() { "s0" } else { names }.chars() {
+					let selection = match name {
+						'c'       => Clipboard::System,
+						'p' | 's' => Clipboard::Primary,
+						'q'       => Clipboard::Secondary,
+						_         => continue,
+					};
+
+					if !selections.contains(&selection) {
+						selections.push(selection);
+					}
+				}
+
+				if data == "?" {
+					// Only one selection can be answered.
+					if let Some(&name) = selections.first() {
+						if self.config.input().clipboard() {
+							self.query = Some((name, Instant::now()));
+							actions.push(Action::Query(name));
+						}
+					}
+				}
+				else if let Some(value) = util::base64::decode(data) {
+					let value = String::from_utf8_lossy(&value).into_owned();
+
+					for name in selections {
+						actions.push(Action::Copy(name, value.clone()));
+					}
+				}
+			}
+
+			"4" => {
+				let mut parts = args.split(';');
+
+				while let (Some(index), Some(spec)) = (parts.next(), parts.next()) {
+					let index = r#try!(break index.parse::<u8>());
+
+					if spec == "?" {
+						write!(output, "\x1B]4;{};{}\x1B\\", index,
+							config::util::from_rgb(self.config.color().get(index)))?;
+					}
+					else if let Some(color) = config::util::to_rgb(spec) {
+						Arc::make_mut(&mut self.config).color_mut().set(index, color);
+						self.touched.all();
+					}
+				}
+			}
+
+			"104" => {
+				let defaults = self.defaults.clone();
+				let indices  = args.split(';').filter_map(|v| v.parse::<u8>().ok()).collect::<Vec<_>>();
+				let palette  = Arc::make_mut(&mut self.config).color_mut();
+
+				if indices.is_empty() {
+					*palette = defaults.color().clone();
+				}
+				else {
+					for index in indices {
+						palette.set(index, *defaults.color().get(index));
+					}
+				}
+
+				self.touched.all();
+			}
+
+			"10" | "11" | "12" => {
+				// Multiple specifications set the following dynamic colors in order.
+				for (id, spec) in (id.parse::<u8>().unwrap() ..= 12).zip(args.split(';')) {
+					if spec == "?" {
+						let color = match id {
+							10 => *self.config.style().color().foreground(),
+							11 => *self.config.style().color().background(),
+							_  => *self.cursor.background(),
+						};
+
+						write!(output, "\x1B]{};{}\x1B\\", id, config::util::from_rgb(&color))?;
+					}
+					else if let Some(color) = config::util::to_rgb(spec) {
+						match id {
+							10 => Arc::make_mut(&mut self.config).style_mut().color_mut().set_foreground(color),
+							11 => Arc::make_mut(&mut self.config).style_mut().color_mut().set_background(color),
+							_  => self.cursor.background = color,
+						}
+
+						self.touched.all();
+					}
+				}
+			}
+
+			"110" => {
+				let color = *self.defaults.style().color().foreground();
+				Arc::make_mut(&mut self.config).style_mut().color_mut().set_foreground(color);
+				self.touched.all();
+			}
+
+			"111" => {
+				let color = *self.defaults.style().color().background();
+				Arc::make_mut(&mut self.config).style_mut().color_mut().set_background(color);
+				self.touched.all();
+			}
+
+			"112" => {
+				self.cursor.background = *self.defaults.style().cursor().background();
+				self.touched.push(term!(self; cursor));
+			}
+
+			"7" => {
+				// Only accept directories on this machine.
+				if args.starts_with("file://") {
+					let rest = &args[7..];
+					let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+
+					if host.is_empty() || host == "localhost" || Some(host) == util::hostname().as_ref().map(AsRef::as_ref) {
+						if !path.is_empty() {
+							self.directory = Some(util::percent_decode(path).into());
+						}
+					}
+					else {
+						self.directory = None;
+					}
+				}
+			}
+
+			"8" => {
+				let mut parts  = args.splitn(2, ';');
+				let     params = parts.next().unwrap_or("");
+				let     uri    = parts.next().unwrap_or("");
+
+				if uri.is_empty() {
+					self.link = None;
+				}
+				else {
+					let id = params.split(':')
+						.find(|p| p.starts_with("id="))
+						.map(|p| &p[3..]);
+
+					self.link = Some(self.links.get(id, uri));
+				}
+			}
+
+			"133" => {
+				let mut parts = args.split(';');
+
+				let mark = match parts.next() {
+					Some("A") => Some(Mark::Prompt),
+					Some("B") => Some(Mark::Command),
+					Some("C") => Some(Mark::Output),
+					Some("D") => Some(Mark::End(parts.next().and_then(|v| v.parse().ok()))),
+					_         => None,
+				};
+
+				if let Some(mark) = mark {
+					self.grid.mark(self.cursor.y(), mark);
+				}
 			}
 
-			cmd if cmd.starts_with("cursor:") => {
-				let mut parts = cmd.split(':').skip(1);
+			_ if command.starts_with("cursor:") => {
+				let mut parts = command.split(':').skip(1);
 
 				match parts.next() {
 					Some("fg") => {
@@ -1856,8 +3500,8 @@ impl Terminal {
 				self.touched.push(term!(self; cursor));
 			}
 
-			cmd if cmd.starts_with("clipboard:") => {
-				let mut parts = cmd.split(':').skip(1);
+			_ if command.starts_with("clipboard:") => {
+				let mut parts = command.split(':').skip(1);
 
 				match parts.next() {
 					Some("set") => {
@@ -1873,7 +3517,7 @@ impl Terminal {
 			_ => ()
 		}
 
-		actions
+		Ok(actions)
 	}
 }
 
@@ -1884,3 +3528,167 @@ impl Index<(u32, u32)> for Terminal {
 		&self.grid[(x, y)]
 	}
 }
+
+/// Compute the DECRQCRA checksum of the given cells the way xterm does it.
+fn checksum<'a, I: IntoIterator<Item = &'a Cell>>(cells: I) -> u16 {
+	let mut total = 0u32;
+
+	for cell in cells {
+		// Wide character references and empty cells don't count.
+		if let Cell::Occupied { ref value, ref style, .. } = *cell {
+			let attributes = style.attributes();
+
+			total = value.chars().fold(total, |t, c| t.wrapping_add(c as u32));
+
+			if attributes.intersects(style::UNDERLINES) {
+				total = total.wrapping_add(0x10);
+			}
+
+			if attributes.contains(style::REVERSE) {
+				total = total.wrapping_add(0x20);
+			}
+
+			if attributes.contains(style::BLINK) {
+				total = total.wrapping_add(0x40);
+			}
+
+			if attributes.contains(style::BOLD) {
+				total = total.wrapping_add(0x80);
+			}
+		}
+	}
+
+	(total.wrapping_neg() & 0xFFFF) as u16
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn occupied(value: &str, attributes: style::Attributes) -> Cell {
+		Cell::occupied(value.into(), Rc::new(Style { attributes: attributes, .. Default::default() }))
+	}
+
+	fn terminal(width: u32, height: u32) -> Terminal {
+		Terminal::new(Arc::new(Config::default()), (8, 16), (width, height), None).unwrap()
+	}
+
+	fn row(terminal: &Terminal, y: u32) -> String {
+		(0 .. terminal.region.width).map(|x| terminal[(x, y)].value()).collect()
+	}
+
+	#[test]
+	fn checksum_empty() {
+		assert_eq!(checksum(None), 0);
+		assert_eq!(checksum(&[Cell::empty(Default::default()), Cell::reference(1)]), 0);
+	}
+
+	#[test]
+	fn checksum_characters() {
+		assert_eq!(checksum(&[occupied("A", style::NONE)]), 0xFFBF);
+		assert_eq!(checksum(&[occupied("A", style::NONE), occupied("B", style::NONE)]), 0xFF7D);
+		assert_eq!(checksum(&[occupied("漢", style::NONE), Cell::reference(1)]), (0x6F22u32.wrapping_neg() & 0xFFFF) as u16);
+	}
+
+	#[test]
+	fn checksum_attributes() {
+		assert_eq!(checksum(&[occupied("A", style::UNDERLINE)]), 0xFFAF);
+		assert_eq!(checksum(&[occupied("A", style::CURLY)]), 0xFFAF);
+		assert_eq!(checksum(&[occupied("A", style::REVERSE)]), 0xFF9F);
+		assert_eq!(checksum(&[occupied("A", style::BLINK)]), 0xFF7F);
+		assert_eq!(checksum(&[occupied("A", style::BOLD)]), 0xFF3F);
+		assert_eq!(checksum(&[occupied("A", style::ITALIC)]), 0xFFBF);
+	}
+
+	#[test]
+	fn columns_standard() {
+		let mut t = terminal(8, 2);
+
+		t.input("ABCDEF\x1B[3G\x1B[2'~", io::sink()).unwrap();
+		assert_eq!(row(&t, 0), "ABEF    ");
+
+		t.input("\x1B[2G\x1B[3'}", io::sink()).unwrap();
+		assert_eq!(row(&t, 0), "A   BEF ");
+	}
+
+	#[test]
+	fn characters_outside_margins() {
+		let mut t = terminal(8, 2);
+
+		// Setting the margins moves the cursor home, outside of them.
+		t.input("ABCDEF\x1B[?69h\x1B[3;5s", io::sink()).unwrap();
+		t.input("\x1B[2@\x1B[2P", io::sink()).unwrap();
+		assert_eq!(row(&t, 0), "ABCDEF  ");
+
+		t.input("\x1B[4G\x1B[P", io::sink()).unwrap();
+		assert_eq!(row(&t, 0), "ABCE F  ");
+	}
+
+	#[test]
+	fn save_per_screen() {
+		let mut t = terminal(8, 4);
+
+		// Nothing was saved on the primary screen, so restoring goes home.
+		t.input("\x1B[?47h\x1B[2;4H\x1B7\x1B[?47l\x1B[3;3H\x1B8", io::sink()).unwrap();
+		assert_eq!((t.cursor.x(), t.cursor.y()), (0, 0));
+
+		t.input("\x1B[3;3H\x1B7\x1B[?47h\x1B8", io::sink()).unwrap();
+		assert_eq!((t.cursor.x(), t.cursor.y()), (3, 1));
+
+		t.input("\x1B[?47l\x1B[1;1H\x1B8", io::sink()).unwrap();
+		assert_eq!((t.cursor.x(), t.cursor.y()), (2, 2));
+	}
+
+	#[test]
+	fn soft_reset() {
+		let mut t = terminal(8, 4);
+
+		t.input("\x1B[1;31mA\x1B[2;3r\x1B[?6h\x1B7\x1B[!p", io::sink()).unwrap();
+		assert_eq!(**t.cursor.style(), Style::default());
+		assert_eq!(t.cursor.scroll, (0, 3));
+		assert!(!t.cursor.state.contains(cursor::ORIGIN));
+		assert!(t.saved[0].is_none());
+		assert_eq!(row(&t, 0), "A       ");
+	}
+
+	#[test]
+	fn hard_reset() {
+		let mut t     = terminal(8, 4);
+		let     color = *t.config.color().get(1);
+
+		t.input("\x1B]2;title\x1B\\\x1B[22;2t\x1B]4;1;rgb:12/34/56\x1B\\A\x1B[?1049h", io::sink()).unwrap();
+		assert_ne!(*t.config.color().get(1), color);
+		assert_eq!(t.titles.len(), 1);
+
+		t.input("\x1Bc", io::sink()).unwrap();
+		assert_eq!(*t.config.color().get(1), color);
+		assert!(t.titles.is_empty());
+		assert_eq!(t.title, "cancer");
+		assert!(!t.mode.contains(mode::ALTERNATE));
+		assert_eq!(row(&t, 0), "        ");
+	}
+
+	#[test]
+	fn rectangle_underlines() {
+		let mut t = terminal(8, 2);
+
+		t.input("\x1B[4:3mAB\x1B[1;1;1;1;24$r", io::sink()).unwrap();
+		assert!(!t[(0, 0)].style().attributes().intersects(style::UNDERLINES));
+
+		t.input("\x1B[1;2;1;2;4$t", io::sink()).unwrap();
+		assert!(!t[(1, 0)].style().attributes().intersects(style::UNDERLINES));
+
+		t.input("\x1B[1;1;1;1;4$t", io::sink()).unwrap();
+		assert_eq!(t[(0, 0)].style().attributes() & style::UNDERLINES, style::UNDERLINE);
+	}
+
+	#[test]
+	fn screen_size() {
+		let mut t      = terminal(8, 4);
+		let mut output = Vec::new();
+
+		t.set_screen(800, 480);
+		t.input("\x1B[18t\x1B[19t", &mut output).unwrap();
+		assert_eq!(output, b"\x1B[8;4;8t\x1B[9;30;100t");
+	}
+}
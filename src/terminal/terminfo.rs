@@ -0,0 +1,247 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// The terminfo source installed for cancer.
+const SOURCE: &str = include_str!("../../assets/cancer.info");
+
+/// The parsed entries, only parsed on the first lookup.
+static ENTRIES: OnceLock<HashMap<&'static str, Vec<&'static str>>> = OnceLock::new();
+
+/// The termcap names of the capabilities, XTGETTCAP accepts them as well.
+const ALIASES: &[(&str, &str)] = &[
+	("am", "am"),     ("ut", "bce"),    ("xn", "xenl"),   ("mi", "mir"),    ("ms", "msgr"),
+	("eo", "eo"),     ("NP", "npc"),    ("co", "cols"),   ("li", "lines"),  ("it", "it"),
+	("Co", "colors"), ("pa", "pairs"),  ("bl", "bel"),    ("vb", "flash"),  ("cr", "cr"),
+	("ta", "ht"),     ("cl", "clear"),  ("ho", "home"),   ("cm", "cup"),    ("ch", "hpa"),
+	("cv", "vpa"),    ("cs", "csr"),    ("up", "cuu1"),   ("do", "cud1"),   ("nd", "cuf1"),
+	("le", "cub1"),   ("UP", "cuu"),    ("DO", "cud"),    ("RI", "cuf"),    ("LE", "cub"),
+	("bt", "cbt"),    ("ce", "el"),     ("cb", "el1"),    ("cd", "ed"),     ("ec", "ech"),
+	("al", "il1"),    ("AL", "il"),     ("dl", "dl1"),    ("DL", "dl"),     ("ic", "ich1"),
+	("IC", "ich"),    ("dc", "dch1"),   ("DC", "dch"),    ("im", "smir"),   ("ei", "rmir"),
+	("sf", "ind"),    ("sr", "ri"),     ("SF", "indn"),   ("SR", "rin"),    ("sc", "sc"),
+	("rc", "rc"),     ("st", "hts"),    ("ct", "tbc"),    ("vi", "civis"),  ("ve", "cnorm"),
+	("vs", "cvvis"),  ("ti", "smcup"),  ("te", "rmcup"),  ("ks", "smkx"),   ("ke", "rmkx"),
+	("SA", "smam"),   ("RA", "rmam"),   ("as", "smacs"),  ("ae", "rmacs"),  ("ac", "acsc"),
+	("eA", "enacs"),  ("md", "bold"),   ("mh", "dim"),    ("mb", "blink"),  ("mr", "rev"),
+	("mk", "invis"),  ("ZH", "sitm"),   ("ZR", "ritm"),   ("so", "smso"),   ("se", "rmso"),
+	("us", "smul"),   ("ue", "rmul"),   ("sa", "sgr"),    ("me", "sgr0"),   ("op", "op"),
+	("AF", "setaf"),  ("AB", "setab"),  ("Sf", "setf"),   ("Sb", "setb"),   ("r1", "rs1"),
+	("r2", "rs2"),    ("kb", "kbs"),    ("kD", "kdch1"),  ("kI", "kich1"),  ("kh", "khome"),
+	("@7", "kend"),   ("kN", "knp"),    ("kP", "kpp"),    ("ku", "kcuu1"),  ("kd", "kcud1"),
+	("kr", "kcuf1"),  ("kl", "kcub1"),  ("kB", "kcbt"),   ("@8", "kent"),   ("Km", "kmous"),
+	("kF", "kind"),   ("kR", "kri"),    ("kC", "kclr"),   ("kL", "kdl1"),   ("kA", "kil1"),
+	("kS", "ked"),    ("kE", "kel"),    ("kM", "krmir"),  ("K1", "ka1"),    ("K3", "ka3"),
+	("K2", "kb2"),    ("K4", "kc1"),    ("K5", "kc3"),    ("k1", "kf1"),    ("k2", "kf2"),
+	("k3", "kf3"),    ("k4", "kf4"),    ("k5", "kf5"),    ("k6", "kf6"),    ("k7", "kf7"),
+	("k8", "kf8"),    ("k9", "kf9"),    ("k;", "kf10"),   ("F1", "kf11"),   ("F2", "kf12"),
+	("F3", "kf13"),   ("F4", "kf14"),   ("F5", "kf15"),   ("F6", "kf16"),   ("F7", "kf17"),
+	("F8", "kf18"),   ("F9", "kf19"),   ("FA", "kf20"),   ("FB", "kf21"),   ("FC", "kf22"),
+	("FD", "kf23"),   ("FE", "kf24"),   ("FF", "kf25"),   ("FG", "kf26"),   ("FH", "kf27"),
+	("FI", "kf28"),   ("FJ", "kf29"),   ("FK", "kf30"),   ("FL", "kf31"),   ("FM", "kf32"),
+	("FN", "kf33"),   ("FO", "kf34"),   ("FP", "kf35"),   ("FQ", "kf36"),   ("FR", "kf37"),
+	("FS", "kf38"),   ("FT", "kf39"),   ("FU", "kf40"),   ("FV", "kf41"),   ("FW", "kf42"),
+	("FX", "kf43"),   ("FY", "kf44"),   ("FZ", "kf45"),   ("Fa", "kf46"),   ("Fb", "kf47"),
+	("Fc", "kf48"),   ("Fd", "kf49"),   ("Fe", "kf50"),   ("Ff", "kf51"),   ("Fg", "kf52"),
+	("Fh", "kf53"),   ("Fi", "kf54"),   ("Fj", "kf55"),   ("Fk", "kf56"),   ("Fl", "kf57"),
+	("Fm", "kf58"),   ("Fn", "kf59"),   ("Fo", "kf60"),   ("Fp", "kf61"),   ("Fq", "kf62"),
+	("Fr", "kf63"),
+];
+
+/// A capability value.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Capability {
+	Boolean,
+	Number(u32),
+	String(Vec<u8>),
+}
+
+/// Look up a capability for the given terminal, following `use=` and
+/// cancellations the same way `tic` does.
+pub fn get(term: &str, name: &str) -> Option<Capability> {
+	let entries = ENTRIES.get_or_init(entries);
+	let term    = if entries.contains_key(term) { term } else { "cancer-256color" };
+
+	lookup(entries, term, name)
+}
+
+/// Get the terminfo name of the given termcap name.
+pub fn alias(name: &str) -> Option<&'static str> {
+	ALIASES.iter().find(|&&(cap, _)| cap == name).map(|&(_, info)| info)
+}
+
+/// Parse the entries in the source, keyed by each of their names.
+fn entries() -> HashMap<&'static str, Vec<&'static str>> {
+	let mut entries = HashMap::new();
+	let mut names   = Vec::new();
+
+	for line in SOURCE.lines() {
+		if line.trim().is_empty() || line.trim_start().starts_with('#') {
+			continue;
+		}
+
+		// A new entry starts, the last name is the description.
+		if !line.starts_with(char::is_whitespace) {
+			names = line.trim_end().trim_end_matches(',').split('|').collect();
+
+			if names.len() > 1 {
+				names.pop();
+			}
+
+			for name in &names {
+				entries.insert(*name, Vec::new());
+			}
+
+			continue;
+		}
+
+		for capability in split(line) {
+			for name in &names {
+				entries.get_mut(name).unwrap().push(capability);
+			}
+		}
+	}
+
+	entries
+}
+
+/// Split the capabilities on a line, taking escaped commas into account.
+fn split(line: &str) -> Vec<&str> {
+	let mut result = Vec::new();
+	let mut start  = 0;
+	let mut escape = false;
+
+	for (i, ch) in line.char_indices() {
+		if escape {
+			escape = false;
+			continue;
+		}
+
+		match ch {
+			'\\' =>
+				escape = true,
+
+			',' => {
+				let capability = line[start .. i].trim();
+
+				if !capability.is_empty() {
+					result.push(capability);
+				}
+
+				start = i + 1;
+			}
+
+			_ => ()
+		}
+	}
+
+	result
+}
+
+fn lookup(entries: &HashMap<&str, Vec<&str>>, term: &str, name: &str) -> Option<Capability> {
+	let capabilities = entries.get(term)?;
+
+	// Local capabilities and cancellations take precedence.
+	for capability in capabilities {
+		if capability.starts_with("use=") {
+			continue;
+		}
+
+		if let Some(rest) = capability.strip_prefix(name) {
+			match rest.chars().next() {
+				None =>
+					return Some(Capability::Boolean),
+
+				Some('@') if rest.len() == 1 =>
+					return None,
+
+				Some('#') =>
+					return rest[1..].parse().ok().map(Capability::Number),
+
+				Some('=') =>
+					return Some(Capability::String(unescape(&rest[1..]))),
+
+				_ => ()
+			}
+		}
+	}
+
+	capabilities.iter()
+		.filter(|c| c.starts_with("use="))
+		.filter_map(|c| lookup(entries, &c[4..], name))
+		.next()
+}
+
+/// Turn the terminfo escapes into the actual bytes.
+fn unescape(value: &str) -> Vec<u8> {
+	let mut result = Vec::new();
+	let mut chars  = value.chars().peekable();
+
+	while let Some(ch) = chars.next() {
+		match ch {
+			'\\' => match chars.next() {
+				Some('E') | Some('e') => result.push(0x1B),
+				Some('n') | Some('l') => result.push(b'\n'),
+				Some('r')             => result.push(b'\r'),
+				Some('t')             => result.push(b'\t'),
+				Some('b')             => result.push(0x08),
+				Some('f')             => result.push(0x0C),
+				Some('s')             => result.push(b' '),
+
+				Some(digit @ '0' ..= '7') => {
+					let mut value = digit.to_digit(8).unwrap();
+
+					for _ in 0 .. 2 {
+						match chars.peek().and_then(|c| c.to_digit(8)) {
+							Some(digit) => {
+								value = value * 8 + digit;
+								chars.next();
+							}
+
+							None =>
+								break
+						}
+					}
+
+					// A NUL is encoded as \200.
+					result.push(if value == 0 { 0x80 } else { value as u8 });
+				}
+
+				Some(ch) => {
+					let mut buffer = [0; 4];
+					result.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+				}
+
+				None => ()
+			},
+
+			'^' => match chars.next() {
+				Some('?') => result.push(0x7F),
+				Some(ch)  => result.push(ch as u8 & 0x1F),
+				None      => result.push(b'^'),
+			},
+
+			ch => {
+				let mut buffer = [0; 4];
+				result.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+			}
+		}
+	}
+
+	result
+}
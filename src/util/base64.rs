@@ -0,0 +1,134 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode the given bytes.
+pub fn encode<T: AsRef<[u8]>>(input: T) -> String {
+	let     input  = input.as_ref();
+	let mut output = String::with_capacity((input.len() + 2) / 3 * 4);
+
+	for chunk in input.chunks(3) {
+		let a = chunk[0] as u32;
+		let b = chunk.get(1).cloned().unwrap_or(0) as u32;
+		let c = chunk.get(2).cloned().unwrap_or(0) as u32;
+		let n = (a << 16) | (b << 8) | c;
+
+		output.push(TABLE[(n >> 18) as usize & 0x3F] as char);
+		output.push(TABLE[(n >> 12) as usize & 0x3F] as char);
+
+		if chunk.len() > 1 {
+			output.push(TABLE[(n >> 6) as usize & 0x3F] as char);
+		}
+		else {
+			output.push('=');
+		}
+
+		if chunk.len() > 2 {
+			output.push(TABLE[n as usize & 0x3F] as char);
+		}
+		else {
+			output.push('=');
+		}
+	}
+
+	output
+}
+
+/// Decode the given string, ignoring whitespace and missing padding.
+pub fn decode<T: AsRef<[u8]>>(input: T) -> Option<Vec<u8>> {
+	let mut output = Vec::with_capacity(input.as_ref().len() / 4 * 3);
+	let mut buffer = 0u32;
+	let mut bits   = 0;
+
+	for &byte in input.as_ref() {
+		let value = match byte {
+			b'A' ..= b'Z' => byte - b'A',
+			b'a' ..= b'z' => byte - b'a' + 26,
+			b'0' ..= b'9' => byte - b'0' + 52,
+			b'+' | b'-'   => 62,
+			b'/' | b'_'   => 63,
+
+			b'=' =>
+				break,
+
+			b' ' | b'\t' | b'\r' | b'\n' =>
+				continue,
+
+			_ =>
+				return None
+		};
+
+		buffer = (buffer << 6) | value as u32;
+		bits  += 6;
+
+		if bits >= 8 {
+			bits -= 8;
+			output.push((buffer >> bits) as u8);
+			buffer &= (1 << bits) - 1;
+		}
+	}
+
+	Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encode_padding() {
+		assert_eq!(encode(""), "");
+		assert_eq!(encode("f"), "Zg==");
+		assert_eq!(encode("fo"), "Zm8=");
+		assert_eq!(encode("foo"), "Zm9v");
+		assert_eq!(encode("foobar"), "Zm9vYmFy");
+		assert_eq!(encode(&[0xFBu8, 0xFF]), "+/8=");
+	}
+
+	#[test]
+	fn decode_padding() {
+		assert_eq!(decode("Zg==").unwrap(), b"f");
+		assert_eq!(decode("Zm8=").unwrap(), b"fo");
+		assert_eq!(decode("Zm9vYmFy").unwrap(), b"foobar");
+		assert_eq!(decode("Zm8").unwrap(), b"fo");
+	}
+
+	#[test]
+	fn decode_alphabets() {
+		assert_eq!(decode("+/8=").unwrap(), &[0xFB, 0xFF]);
+		assert_eq!(decode("-_8=").unwrap(), &[0xFB, 0xFF]);
+	}
+
+	#[test]
+	fn decode_whitespace() {
+		assert_eq!(decode("Zm9v\nYmFy\r\n").unwrap(), b"foobar");
+		assert_eq!(decode(" Zm 9v\t").unwrap(), b"foo");
+	}
+
+	#[test]
+	fn decode_invalid() {
+		assert_eq!(decode("Zm9v!"), None);
+		assert_eq!(decode("Zm.v"), None);
+	}
+
+	#[test]
+	fn roundtrip() {
+		let data = (0 ..= 255u8).collect::<Vec<_>>();
+		assert_eq!(decode(encode(&data)).unwrap(), data);
+	}
+}
@@ -0,0 +1,79 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+const TABLE: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Encode the given bytes.
+pub fn encode<T: AsRef<[u8]>>(input: T) -> String {
+	let     input  = input.as_ref();
+	let mut output = String::with_capacity(input.len() * 2);
+
+	for &byte in input {
+		output.push(TABLE[(byte >> 4) as usize] as char);
+		output.push(TABLE[(byte & 0xF) as usize] as char);
+	}
+
+	output
+}
+
+/// Decode the given string.
+pub fn decode<T: AsRef<[u8]>>(input: T) -> Option<Vec<u8>> {
+	let input = input.as_ref();
+
+	if input.len() % 2 != 0 {
+		return None;
+	}
+
+	input.chunks(2).map(|pair| {
+		let high = (pair[0] as char).to_digit(16)?;
+		let low  = (pair[1] as char).to_digit(16)?;
+
+		Some((high << 4 | low) as u8)
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encode_bytes() {
+		assert_eq!(encode(""), "");
+		assert_eq!(encode("TN"), "544E");
+		assert_eq!(encode(&[0x00u8, 0x0F, 0xAB, 0xFF]), "000FABFF");
+	}
+
+	#[test]
+	fn decode_bytes() {
+		assert_eq!(decode("544E").unwrap(), b"TN");
+		assert_eq!(decode("000fabFF").unwrap(), &[0x00, 0x0F, 0xAB, 0xFF]);
+		assert_eq!(decode("").unwrap(), b"");
+	}
+
+	#[test]
+	fn decode_invalid() {
+		assert_eq!(decode("544"), None);
+		assert_eq!(decode("5G"), None);
+		assert_eq!(decode("+1"), None);
+	}
+
+	#[test]
+	fn roundtrip() {
+		let data = (0 ..= 255u8).collect::<Vec<_>>();
+		assert_eq!(decode(encode(&data)).unwrap(), data);
+	}
+}
@@ -15,9 +15,21 @@
 // You should have received a copy of the GNU General Public License
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::str;
+use std::env;
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::ffi::CStr;
+use libc::{c_char, gethostname, getuid};
+
 #[macro_use]
 mod macros;
 
+pub mod base64;
+pub mod hex;
+
 pub fn clamp<T: PartialOrd>(n: T, min: T, max: T) -> T {
 	if n > max {
 		max
@@ -29,3 +41,90 @@ pub fn clamp<T: PartialOrd>(n: T, min: T, max: T) -> T {
 		n
 	}
 }
+
+/// Get the host name of the machine.
+pub fn hostname() -> Option<String> {
+	let mut buffer = [0 as c_char; 256];
+
+	unsafe {
+		if gethostname(buffer.as_mut_ptr(), buffer.len()) != 0 {
+			return None;
+		}
+
+		buffer[buffer.len() - 1] = 0;
+		Some(CStr::from_ptr(buffer.as_ptr()).to_string_lossy().into_owned())
+	}
+}
+
+/// Save a file sent by a program in the private download directory, existing
+/// files are never overwritten and links are never followed.
+pub fn save(name: Option<&str>, content: &[u8]) -> io::Result<PathBuf> {
+	let uid       = unsafe { getuid() };
+	let directory = env::temp_dir().join(format!("cancer-{}", uid));
+
+	match DirBuilder::new().mode(0o700).create(&directory) {
+		Ok(()) => (),
+
+		Err(ref error) if error.kind() == io::ErrorKind::AlreadyExists => {
+			let metadata = fs::symlink_metadata(&directory)?;
+
+			if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+				return Err(io::Error::new(io::ErrorKind::PermissionDenied, "insecure download directory"));
+			}
+		}
+
+		Err(error) =>
+			return Err(error),
+	}
+
+	let name = name
+		.and_then(|n| Path::new(n).file_name())
+		.map(|n| n.to_string_lossy().into_owned())
+		.unwrap_or_else(|| String::from("download"));
+
+	for i in 0 .. 100 {
+		let path = if i == 0 {
+			directory.join(&name)
+		}
+		else {
+			directory.join(format!("{}.{}", name, i))
+		};
+
+		match OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path) {
+			Ok(mut file) => {
+				file.write_all(content)?;
+				return Ok(path);
+			}
+
+			Err(ref error) if error.kind() == io::ErrorKind::AlreadyExists =>
+				continue,
+
+			Err(error) =>
+				return Err(error),
+		}
+	}
+
+	Err(io::Error::new(io::ErrorKind::AlreadyExists, "too many files with the same name"))
+}
+
+/// Decode a percent-encoded string.
+pub fn percent_decode(value: &str) -> String {
+	let mut output = Vec::with_capacity(value.len());
+	let mut bytes  = value.bytes();
+
+	while let Some(byte) = bytes.next() {
+		if byte == b'%' {
+			let hex = bytes.clone().take(2).collect::<Vec<u8>>();
+
+			if let Some(decoded) = str::from_utf8(&hex).ok().filter(|v| v.len() == 2).and_then(|v| u8::from_str_radix(v, 16).ok()) {
+				output.push(decoded);
+				bytes.nth(1);
+				continue;
+			}
+		}
+
+		output.push(byte);
+	}
+
+	String::from_utf8_lossy(&output).into_owned()
+}